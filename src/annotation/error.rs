@@ -0,0 +1,217 @@
+//!
+//! `ParseError` is the error from parsing an annotation line into one of the base rows, and from there into a
+//! `GoAssociation`.
+//!
+//! Most errors are about a single column, so `ParseError::Column` carries the column number (counting from 1, as in the
+//! format specifications), the column name, the value that failed, and a `ParseErrorKind` saying what was wrong with it.
+//! Displayed, this reads like "column 14 (Date): `2020-13-01` is not YYYYMMDD".
+//!
+//! The field types in `fields` still fail with a `String`, and `parse_column` turns that into a `ParseError` for the
+//! column the field came from.
+//!
+//! Each column of a line is parsed independently, and every failure is gathered into `ParseErrors`, so a line with a
+//! bad date and a bad CURIE reports both rather than just the first.
+//!
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::vec;
+
+/// Descriptions of the forms of common columns, for `ParseErrorKind::Malformed`.
+pub mod form {
+    pub const NO_SPACES: &str = "a value without spaces";
+    pub const CURIE: &str = "a CURIE like `GO:0005634`";
+    pub const GO_TERM: &str = "a GO term";
+    pub const ECO: &str = "an ECO CURIE like `ECO:0000314`";
+    pub const CURIE_LIST: &str = "a `|` separated list of CURIEs";
+    pub const WITH_FROM: &str = "a `|` separated list of `,` separated CURIEs";
+    pub const EVIDENCE_CODE: &str = "an evidence code like `IDA`";
+    pub const ASPECT: &str = "`C`, `F`, or `P`";
+    pub const TEXT: &str = "text";
+    pub const TEXT_LIST: &str = "a `|` separated list";
+    pub const GAF_TAXON: &str = "a taxon like `taxon:9606`, with an optional interacting taxon like `taxon:9606|taxon:10090`";
+    pub const DATE: &str = "YYYYMMDD";
+    pub const ISO_DATE: &str = "YYYY-MM-DD or YYYY-MM-DDTHH:MM:SS";
+    pub const QUALIFIER: &str = "a relation, optionally after `NOT` like `NOT|enables`";
+    pub const GAF_2_1_QUALIFIER: &str = "`NOT`, a relation, or both like `NOT|contributes_to`";
+    pub const NOT: &str = "`NOT`";
+    pub const EXTENSIONS: &str = "extensions like `part_of(GO:0005634),occurs_in(CL:0000057)`";
+    pub const PROPERTIES: &str = "a `|` separated list of `key=value` properties";
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParseErrorKind {
+    /// The column must have a value, but is empty
+    Empty,
+    /// The value isn't of the expected form, which is described like `YYYYMMDD` or `a CURIE`
+    Malformed(&'static str),
+    /// The value has the right form, but isn't known in the `Context`, like a relation label with no CURIE
+    Unknown(&'static str)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParseError {
+    Column {
+        column: usize,
+        name: &'static str,
+        value: String,
+        kind: ParseErrorKind
+    },
+    /// The line has the wrong number of columns for its format, which has `min` required columns and `max` in all
+    ColumnCount {
+        min: usize,
+        max: usize,
+        found: usize
+    },
+    /// A problem with the line as a whole, rather than any one column
+    Line(String)
+}
+
+impl ParseError {
+    pub fn column<S: Into<String>>(column: usize, name: &'static str, value: S, kind: ParseErrorKind) -> ParseError {
+        ParseError::Column {
+            column,
+            name,
+            value: value.into(),
+            kind
+        }
+    }
+
+    /// The name of the column the error is in, or None if the error isn't about a single column.
+    pub fn column_name(&self) -> Option<&'static str> {
+        match self {
+            ParseError::Column { name, .. } => Some(name),
+            _ => None
+        }
+    }
+
+    /// The offending value, or None if the error isn't about a single column.
+    pub fn value(&self) -> Option<&str> {
+        match self {
+            ParseError::Column { value, .. } => Some(value),
+            _ => None
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Column { column, name, kind: ParseErrorKind::Empty, .. } => write!(f, "column {} ({}) is empty", column, name),
+            ParseError::Column { column, name, value, kind: ParseErrorKind::Malformed(form) } => write!(f, "column {} ({}): `{}` is not {}", column, name, value, form),
+            ParseError::Column { column, name, value, kind: ParseErrorKind::Unknown(what) } => write!(f, "column {} ({}): `{}` is not a known {}", column, name, value, what),
+            ParseError::ColumnCount { min, max, found } if min == max => write!(f, "expected {} columns, but found {}", max, found),
+            ParseError::ColumnCount { min, max, found } => write!(f, "expected {} to {} columns, but found {}", min, max, found),
+            ParseError::Line(message) => write!(f, "{}", message)
+        }
+    }
+}
+
+///
+/// All the `ParseError`s from one line.
+/// 
+/// Columns are parsed with `check`, which keeps any error and gives back the parsed value as an `Option`. Once every
+/// column is checked, the line can be built if all of them are `Some`, and otherwise the `ParseErrors` is the error.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ParseErrors(Vec<ParseError>);
+
+impl ParseErrors {
+    pub fn new() -> ParseErrors {
+        ParseErrors(vec![])
+    }
+
+    /// Keeps the error from `result`, if there is one, and returns the parsed value otherwise.
+    pub fn check<T>(&mut self, result: Result<T, ParseError>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(err) => {
+                self.0.push(err);
+                None
+            }
+        }
+    }
+
+    pub fn errors(&self) -> &[ParseError] {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl From<ParseError> for ParseErrors {
+    fn from(error: ParseError) -> ParseErrors {
+        ParseErrors(vec![error])
+    }
+}
+
+impl IntoIterator for ParseErrors {
+    type Item = ParseError;
+    type IntoIter = vec::IntoIter<ParseError>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl fmt::Display for ParseErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let errors: Vec<String> = self.0.iter().map(|e| e.to_string()).collect();
+        write!(f, "{}", errors.join("; "))
+    }
+}
+
+///
+/// Parses the `value` of a column as a `T`. If it fails, the error is `Empty` if the value is empty, and otherwise
+/// `Malformed`, described by `form`.
+pub fn parse_column<'a, T: TryFrom<&'a str, Error=String>>(column: usize, name: &'static str, value: &'a str, form: &'static str) -> Result<T, ParseError> {
+    parse_column_with(column, name, value, form, T::try_from)
+}
+
+/// `parse_column` with the given `parse` function, for types like `Date` that are parsed differently by each format.
+pub fn parse_column_with<'a, T, F: FnOnce(&'a str) -> Result<T, String>>(column: usize, name: &'static str, value: &'a str, form: &'static str, parse: F) -> Result<T, ParseError> {
+    parse(value).map_err(|_| {
+        let kind = if value.is_empty() { ParseErrorKind::Empty } else { ParseErrorKind::Malformed(form) };
+        ParseError::column(column, name, value, kind)
+    })
+}
+
+/// `parse_column` for a column that may be empty, which is None.
+pub fn parse_optional_column<'a, T: TryFrom<&'a str, Error=String>>(column: usize, name: &'static str, value: &'a Option<String>, form: &'static str) -> Result<Option<T>, ParseError> {
+    match value {
+        None => Ok(None),
+        Some(v) => parse_column(column, name, v.as_str(), form).map(Some)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::annotation::fields::{Curie, Date};
+
+    #[test]
+    fn test_parse_column() {
+        let date: Result<Date, ParseError> = parse_column_with(14, "Date", "2020-13-01", "YYYYMMDD", Date::from_gaf);
+        assert_eq!(date.unwrap_err().to_string(), "column 14 (Date): `2020-13-01` is not YYYYMMDD");
+
+        let curie: Result<Curie, ParseError> = parse_column(5, "GO ID", "", "a CURIE");
+        assert_eq!(curie.unwrap_err(), ParseError::column(5, "GO ID", "", ParseErrorKind::Empty));
+
+        let missing: Result<Option<Curie>, ParseError> = parse_optional_column(17, "Gene Product Form ID", &None, "a CURIE");
+        assert_eq!(missing, Ok(None));
+    }
+
+    #[test]
+    fn test_parse_errors_keeps_every_error() {
+        let mut errors = ParseErrors::new();
+        let curie: Option<Curie> = errors.check(parse_column(5, "GO ID", "GO:0005634", form::CURIE));
+        let date: Option<Date> = errors.check(parse_column_with(14, "Date", "2020-13-01", form::DATE, Date::from_gaf));
+        let with: Option<Curie> = errors.check(parse_column(8, "With (or) From", "bad", form::CURIE));
+
+        assert_eq!(curie, Some(Curie::new("GO", "0005634")));
+        assert!(date.is_none() && with.is_none());
+        assert_eq!(errors.errors().iter().map(|e| e.column_name().unwrap()).collect::<Vec<_>>(), vec!["Date", "With (or) From"]);
+        assert_eq!(errors.to_string(), "column 14 (Date): `2020-13-01` is not YYYYMMDD; column 8 (With (or) From): `bad` is not a CURIE like `GO:0005634`");
+    }
+}
@@ -30,6 +30,10 @@
 //! 
 //! `Property` represents a key-value pair, as seen in GPAD, for example. 
 //! 
+//! All of these can be serialized with serde for JSON output. Curies, Dates, and Aspects serialize as the strings they are
+//! written as in the annotation files, and are checked with the same `TryFrom` parsing when deserialized. Lists,
+//! Conjunctions, and ClassExpressions serialize as structured arrays and objects.
+//! 
 
 use std::convert::TryFrom;
 use std::str::FromStr;
@@ -38,6 +42,17 @@ use chrono::prelude::*;
 use chrono::Utc;
 use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+/// Deserializes a string field with its `TryFrom<&str>` parser, so values from JSON are checked the same way as columns are.
+fn deserialize_with_try_from<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: for<'a> TryFrom<&'a str, Error=String> {
+
+    let s = String::deserialize(deserializer)?;
+    T::try_from(s.as_str()).map_err(de::Error::custom)
+}
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Curie {
@@ -68,6 +83,18 @@ impl fmt::Display for Curie {
     }
 }
 
+impl Serialize for Curie {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Curie {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Curie, D::Error> {
+        deserialize_with_try_from(deserializer)
+    }
+}
+
 impl TryFrom<&str> for Curie {
     type Error = String;
 
@@ -84,7 +111,8 @@ impl TryFrom<&str> for Curie {
     }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct ListField<I> {
     items: Vec<I>
 }
@@ -228,6 +256,19 @@ impl fmt::Display for Aspect {
     }
 }
 
+impl Serialize for Aspect {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Aspect {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Aspect, D::Error> {
+        let c = SingleChar::deserialize(deserializer)?;
+        Aspect::try_from(c.0).map_err(de::Error::custom)
+    }
+}
+
 impl TryFrom<char> for Aspect {
     type Error = String;
 
@@ -241,7 +282,8 @@ impl TryFrom<char> for Aspect {
     }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(transparent)]
 pub struct NoSpaceString {
     pub value: String
 }
@@ -254,6 +296,12 @@ impl NoSpaceString {
     }
 }
 
+impl<'de> Deserialize<'de> for NoSpaceString {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<NoSpaceString, D::Error> {
+        deserialize_with_try_from(deserializer)
+    }
+}
+
 impl TryFrom<&str> for NoSpaceString {
     type Error = String;
 
@@ -406,6 +454,12 @@ impl TryFrom<&str> for Not {
 #[derive(Clone, Debug, PartialEq)]
 pub struct SingleChar(pub char);
 
+impl<'de> Deserialize<'de> for SingleChar {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<SingleChar, D::Error> {
+        deserialize_with_try_from(deserializer)
+    }
+}
+
 impl TryFrom<&str> for SingleChar {
     type Error = String;
 
@@ -418,7 +472,10 @@ impl TryFrom<&str> for SingleChar {
     }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+/// Unlike parsing from a column, an empty `PlainString` is allowed when deserializing, since gene products from GPAD
+/// without a GPI have an empty type.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct PlainString(pub String);
 
 impl fmt::Display for PlainString {
@@ -439,9 +496,52 @@ impl TryFrom<&str> for PlainString {
     }
 }
 
+/// A date, with an optional time of day as seen in GPAD 2.0.
+/// 
+/// `Display` writes the GAF `YYYYMMDD` form, dropping any time, while `iso_string` writes the
+/// GPAD 2.0 `YYYY-MM-DD[THH:MM:SS]` form.
 #[derive(Clone, Debug, PartialEq)]
 pub struct Date {
-    pub date: chrono::Date<Utc>
+    pub date: chrono::Date<Utc>,
+    pub time: Option<NaiveTime>
+}
+
+impl Date {
+    /// Parses the `YYYYMMDD` form used in GAF and GPAD 1.2.
+    pub fn from_gaf(entity: &str) -> Result<Date, String> {
+        if entity.len() != 8 || !entity.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(format!("`{}` is not YYYYMMDD", entity));
+        }
+        NaiveDate::parse_from_str(entity, "%Y%m%d")
+            .map(|d| Date::new(d, None))
+            .map_err(|err| format!("{}", err))
+    }
+
+    /// Parses the extended ISO 8601 `YYYY-MM-DD` and `YYYY-MM-DDTHH:MM:SS` forms used in GPAD 2.0.
+    pub fn from_iso(entity: &str) -> Result<Date, String> {
+        let datetime = match entity.len() {
+            10 => NaiveDate::parse_from_str(entity, "%Y-%m-%d").map(|d| (d, None)),
+            19 => NaiveDateTime::parse_from_str(entity, "%Y-%m-%dT%H:%M:%S").map(|dt| (dt.date(), Some(dt.time()))),
+            _ => return Err(format!("`{}` is not YYYY-MM-DD or YYYY-MM-DDTHH:MM:SS", entity))
+        };
+        datetime
+            .map(|(d, time)| Date::new(d, time))
+            .map_err(|err| format!("{}", err))
+    }
+
+    fn new(date: NaiveDate, time: Option<NaiveTime>) -> Date {
+        Date {
+            date: Utc.from_utc_date(&date),
+            time
+        }
+    }
+
+    pub fn iso_string(&self) -> String {
+        match self.time {
+            Some(time) => format!("{}T{}", self.date.format("%Y-%m-%d"), time.format("%H:%M:%S")),
+            None => self.date.format("%Y-%m-%d").to_string()
+        }
+    }
 }
 
 impl fmt::Display for Date {
@@ -450,19 +550,16 @@ impl fmt::Display for Date {
     }
 }
 
-impl TryFrom<&str> for Date {
-    type Error = String;
+impl Serialize for Date {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.iso_string())
+    }
+}
 
-    fn try_from(entity: &str) -> Result<Date, Self::Error> {
-        let date = NaiveDate::parse_from_str(entity, "%Y%m%d");
-        match date {
-            Ok(d) => {
-                Ok(Date { 
-                    date: Utc.from_utc_date(&d)
-                })
-            },
-            Err(err) => Err(format!("{}", err))
-        }
+impl<'de> Deserialize<'de> for Date {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Date, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Date::from_iso(s.as_str()).map_err(de::Error::custom)
     }
 }
 
@@ -485,7 +582,7 @@ impl fmt::Display for Label {
     }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct ClassExpression<R, F> {
     pub relation: R,
     pub filler: F
@@ -545,7 +642,8 @@ impl<'a, R, F> TryFrom<&'a str> for ClassExpression<R, F>
     }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct Conjunction<C> {
     elements: Vec<C>
 }
@@ -607,9 +705,26 @@ impl<'a, C: TryFrom<&'a str, Error=String> + std::fmt::Debug> TryFrom<&'a str> f
     }
 }
 
-#[derive(PartialEq, Debug, Clone)]
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
 pub struct Property(pub String, pub String);
 
+impl fmt::Display for Property {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}={}", self.0, self.1)
+    }
+}
+
+impl TryFrom<&str> for Property {
+    type Error = String;
+
+    fn try_from(entity: &str) -> Result<Property, Self::Error> {
+        match entity.split_once('=') {
+            Some((key, value)) if !key.is_empty() && !value.is_empty() => Ok(Property(key.into(), value.into())),
+            _ => Err(format!("Property `{}` must take the form `key=value`", entity))
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -665,6 +780,34 @@ mod test {
         assert_eq!(Conjunction::try_from("RO:1234,GO:1234"), Ok(Conjunction::new(vec![Curie::new("RO", "1234"), Curie::new("GO", "1234")])))
     }
 
+    #[test]
+    fn test_date_forms() {
+        let gaf = Date::from_gaf("20180711").unwrap();
+        assert_eq!(gaf.time, None);
+        assert_eq!(gaf.to_string(), "20180711");
+        assert_eq!(gaf.iso_string(), "2018-07-11");
+
+        assert_eq!(Date::from_iso("2018-07-11"), Ok(gaf));
+
+        let with_time = Date::from_iso("2018-07-11T13:04:59").unwrap();
+        assert_eq!(with_time.time, NaiveTime::from_hms_opt(13, 4, 59));
+        assert_eq!(with_time.to_string(), "20180711");
+        assert_eq!(with_time.iso_string(), "2018-07-11T13:04:59");
+
+        assert!(Date::from_gaf("2018-07-11").is_err());
+        assert!(Date::from_gaf("201871").is_err());
+        assert!(Date::from_gaf("20181301").is_err());
+        assert!(Date::from_iso("20180711").is_err());
+        assert!(Date::from_iso("2018-7-11").is_err());
+        assert!(Date::from_iso("2018-13-01").is_err());
+    }
+
+    #[test]
+    fn test_property() {
+        assert_eq!(Property::try_from("contributor=https://orcid.org/0000-0002-1706-4196"), Ok(Property("contributor".into(), "https://orcid.org/0000-0002-1706-4196".into())));
+        assert!(Property::try_from("contributor").is_err());
+    }
+
     #[test]
     fn test_annotation_extension() {
         let extension = "part_of(GO:12345),part_of(MGI:5678)|foo_bar(FB:1234)";
@@ -1,37 +1,97 @@
 use super::fields::*;
-use super::{BaseGaf2_1Row};
+use super::error::{form, ParseError, ParseErrorKind};
+use super::{BaseGaf2_1Row, BaseGaf2_2Row};
 use super::model::{HasSubject, HasRelation, HasTerm, HasEvidence, HasMetadata, HasExtensions, 
     Subject, Relation, Term, Evidence, Metadata, Extensions};
 use crate::meta::Context;
 
-impl HasSubject<String> for BaseGaf2_1Row {
+// GAF 2.1 and GAF 2.2 share every column except the qualifier, so the pieces that
+// look the same across both versions are built by the functions below.
 
-    fn subject(&self, _: &Context) -> Result<Subject, String> {
+fn first_taxon(taxon: &OneOrTwoItems<Curie>) -> Curie {
+    match taxon {
+        OneOrTwoItems::One(t) => t,
+        OneOrTwoItems::Two(t, _) => t
+    }.clone()
+}
+
+fn interacting_taxon(taxon: &OneOrTwoItems<Curie>) -> Option<Curie> {
+    match taxon {
+        OneOrTwoItems::Two(_, t) => Some(t.clone()),
+        _ => None
+    }
+}
+
+fn relation_from_aspect(aspect: Aspect) -> Relation {
+    match aspect {
+        Aspect::BioProcess => Curie::new("RO", "0002331"),
+        Aspect::CellComponent => Curie::new("BFO", "0000050"),
+        Aspect::MolecularFunction => Curie::new("RO", "0002327")
+    }
+}
+
+fn gaf_term(id: &Curie, taxon: &OneOrTwoItems<Curie>) -> Result<Term, ParseError> {
+    if id.same_namespace("GO") {
+        Ok(Term::new(id.clone(), Some(first_taxon(taxon))))
+    } else {
+        Err(ParseError::column(5, "GO ID", id.to_string(), ParseErrorKind::Malformed(form::GO_TERM)))
+    }
+}
+
+fn gaf_evidence(references: &ListField<Curie>, code: EcoCode, withfrom: &ListField<Curie>, context: &Context) -> Result<Evidence, ParseError> {
+    // Convert column index 6, evidence code into an evidence CURIE
+    // Grab the first of any GO_REF Curies in references
+    let goref = references.items()
+        .iter()
+        .find(|curie| curie.same_namespace("GO_REF"));
+    
+    let eco_curie = context.eco_mapping.eco_to_curie(code, goref);
+    if let Some(curie) = eco_curie {
+        let withfrom: ListField<Conjunction<Curie>> = withfrom.map_new(|curie| Conjunction::new(vec![curie.clone()]));
+        Ok(Evidence::new(curie.clone(), references.clone(), withfrom))
+    } else {
+        Err(ParseError::column(7, "Evidence Code", code.to_string(), ParseErrorKind::Unknown("evidence code")))
+    }
+}
+
+fn gaf_extensions(subject: &Option<Curie>, object: &ListField<Conjunction<ClassExpression<Label, Curie>>>, context: &Context) -> Result<Extensions, ParseError> {
+    let subject_extension = subject.as_ref()
+        .map(|sub| ClassExpression::new(Curie::new("rdfs", "subClassOf"), sub.clone()));
+    
+    // Turn ClassExpression with Label into ClassExpression with Curie
+    let map_label_expression = |label_expr: &ClassExpression<Label, Curie>| {
+        
+        let ClassExpression { relation, filler } = label_expr;
+        context.label_to_curie(relation)
+            .ok_or_else(|| ParseError::column(16, "Annotation Extension", relation.0.clone(), ParseErrorKind::Unknown("relation")))
+            .map(|curie_rel| ClassExpression::new(curie_rel, filler.clone()))
+    }; // Result<ClassExpression<Curie, Curie>, ParseError>
+
+    // convert to Result<ListField<Conjunction<ClassExpression<Curie, Curie>>>>
+    let object_extension = object.map_new_results(|conjunctions|
+        conjunctions.map_new_results(|expression|
+            map_label_expression(expression)));
+
+    object_extension.map(|obj_extension| Extensions::new(subject_extension, obj_extension))
+}
+
+impl HasSubject<ParseError> for BaseGaf2_1Row {
+
+    fn subject(&self, _: &Context) -> Result<Subject, ParseError> {
         let id = Curie::new(&self.0.value, &self.1.value);
         let label = self.2.clone();
         let fullname = self.9.clone();
         let synonyms = self.10.clone();
         let kind = self.11.clone();
-        let taxon = Some(match &self.12 {
-            OneOrTwoItems::One(t) => t,
-            OneOrTwoItems::Two(t, _) => t
-        }.clone());
+        let taxon = Some(first_taxon(&self.12));
 
         Ok(Subject::new(id, label, fullname, synonyms, kind, taxon))
     }
 }
 
-impl HasRelation<String> for BaseGaf2_1Row {
+impl HasRelation<ParseError> for BaseGaf2_1Row {
     /// Relation is either from Qualifier, or from Aspect
-    fn relation(&self, context: &Context) -> Result<Relation, String> {
-
-        fn relation_from_aspect(aspect: Aspect) -> Relation {
-            match aspect {
-                Aspect::BioProcess => Curie::new("RO", "0002331"),
-                Aspect::CellComponent => Curie::new("BFO", "0000050"),
-                Aspect::MolecularFunction => Curie::new("RO", "0002327")
-            }
-        }
+    fn relation(&self, context: &Context) -> Result<Relation, ParseError> {
 
         let qualifier_label = match &self.3 {
             Some(qual) => match qual {
@@ -54,53 +114,23 @@ impl HasRelation<String> for BaseGaf2_1Row {
     }
 }
 
-impl HasTerm<String> for BaseGaf2_1Row {
+impl HasTerm<ParseError> for BaseGaf2_1Row {
 
-    fn term(&self, _: &Context) -> Result<Term, String> {
-        
-        if self.4.same_namespace("GO") {
-            let id = self.4.clone();
-            let taxon = Some(match &self.12 {
-                OneOrTwoItems::One(t) => t,
-                OneOrTwoItems::Two(t, _) => t
-            }.clone());
-            Ok(Term::new(id, taxon))
-        } else {
-            Err("Curie must be a GO term".into())
-        }   
+    fn term(&self, _: &Context) -> Result<Term, ParseError> {
+        gaf_term(&self.4, &self.12)
     }
 }
 
-impl HasEvidence<String> for BaseGaf2_1Row {
+impl HasEvidence<ParseError> for BaseGaf2_1Row {
 
-    fn evidence(&self, context: &Context) -> Result<Evidence, String> {
-        // Convert column index 6, evidence code into an evidence CURIE
-        // Grab the first of any GO_REF Curies in references
-        let goref = &self.5.items()
-            .iter()
-            .filter(|curie| curie.same_namespace("GO_REF"))
-            .take(1)
-            .next();
-        
-        let eco_curie = context.eco_mapping.eco_to_curie(self.6, *goref);
-        if let Some(curie) = eco_curie {
-            let references = self.5.clone();
-            let withfrom: ListField<Conjunction<Curie>> = self.7.map_new(|curie| Conjunction::new(vec![curie.clone()]));
-            Ok(Evidence::new(curie.clone(), references, withfrom))
-        } else {
-            Err(format!("Could not find ECO CURIE for `{:?}`", &self.6))
-        }
+    fn evidence(&self, context: &Context) -> Result<Evidence, ParseError> {
+        gaf_evidence(&self.5, self.6, &self.7, context)
     }
 }
 
-impl HasMetadata<String> for BaseGaf2_1Row {
-
-    fn metadata(&self, _: &Context) -> Result<Metadata, String> {
+impl HasMetadata<ParseError> for BaseGaf2_1Row {
 
-        let interacting_taxon = match &self.12 {
-            OneOrTwoItems::Two(_, t) => Some(t.clone()),
-            _ => None
-        };
+    fn metadata(&self, _: &Context) -> Result<Metadata, ParseError> {
 
         let negated = self.3.as_ref().map(|either_both| {
             match either_both {
@@ -113,7 +143,7 @@ impl HasMetadata<String> for BaseGaf2_1Row {
         Ok(Metadata {
             negated,
             aspect: Some(self.8),
-            interacting_taxon,
+            interacting_taxon: interacting_taxon(&self.12),
             provided_by: self.14.clone(),
             date: self.13.clone(),
             properties: ListField::new(vec![])
@@ -122,30 +152,74 @@ impl HasMetadata<String> for BaseGaf2_1Row {
 }
 
 
-impl HasExtensions<String> for BaseGaf2_1Row {
+impl HasExtensions<ParseError> for BaseGaf2_1Row {
 
-    fn extensions(&self, context: &Context) -> Result<Extensions, String> {
+    fn extensions(&self, context: &Context) -> Result<Extensions, ParseError> {
+        gaf_extensions(&self.16, &self.15, context)
+    }
+}
 
-        let subject_extension = self.16.as_ref()
-            .map(|sub| ClassExpression::new(Curie::new("rdfs", "subClassOf"), sub.clone()));
-        
-        // Turn ClassExpression with Label into ClassExpression with Curie
-        let map_label_expression = |label_expr: &ClassExpression<Label, Curie>| {
-            
-            let ClassExpression { relation, filler } = label_expr;
-            context.label_to_curie(&relation)
-                .ok_or(format!("Could not find relation CURIE for `{}`", relation.0))
-                .map(|curie_rel| ClassExpression::new(curie_rel, filler.clone()))
-        }; // Result<ClassExpression<Curie, Curie>, String>
-
-        let object_extension = Ok(&self.15)
-            .and_then(|obj_ext: &ListField<Conjunction<ClassExpression<Label, Curie>>>| 
-                // convert to Result<ListField<Conjunction<ClassExpression<Curie, Curie>>>>
-                obj_ext.map_new_results(|conjunctions|
-                    conjunctions.map_new_results(|expression|
-                        map_label_expression(expression)))
-            );
-
-        object_extension.map(|obj_extension| Extensions::new(subject_extension, obj_extension))
+impl HasSubject<ParseError> for BaseGaf2_2Row {
+
+    fn subject(&self, _: &Context) -> Result<Subject, ParseError> {
+        let id = Curie::new(&self.0.value, &self.1.value);
+        let taxon = Some(first_taxon(&self.12));
+
+        Ok(Subject::new(id, self.2.clone(), self.9.clone(), self.10.clone(), self.11.clone(), taxon))
+    }
+}
+
+impl HasRelation<ParseError> for BaseGaf2_2Row {
+    /// In GAF 2.2 the relation always comes from the Qualifier, so an unknown relation label is an error
+    /// rather than falling back to the Aspect.
+    fn relation(&self, context: &Context) -> Result<Relation, ParseError> {
+        let label = match &self.3 {
+            EitherOrBoth::Right(label) => label,
+            EitherOrBoth::Both(_, label) => label,
+            EitherOrBoth::Left(_) => return Err(ParseError::column(4, "Qualifier", "NOT", ParseErrorKind::Malformed(form::QUALIFIER)))
+        };
+
+        context.label_to_curie(label)
+            .ok_or_else(|| ParseError::column(4, "Qualifier", label.0.clone(), ParseErrorKind::Unknown("relation")))
+    }
+}
+
+impl HasTerm<ParseError> for BaseGaf2_2Row {
+
+    fn term(&self, _: &Context) -> Result<Term, ParseError> {
+        gaf_term(&self.4, &self.12)
+    }
+}
+
+impl HasEvidence<ParseError> for BaseGaf2_2Row {
+
+    fn evidence(&self, context: &Context) -> Result<Evidence, ParseError> {
+        gaf_evidence(&self.5, self.6, &self.7, context)
+    }
+}
+
+impl HasMetadata<ParseError> for BaseGaf2_2Row {
+
+    fn metadata(&self, _: &Context) -> Result<Metadata, ParseError> {
+        let negated = match self.3 {
+            EitherOrBoth::Both(_, _) | EitherOrBoth::Left(_) => true,
+            EitherOrBoth::Right(_) => false
+        };
+
+        Ok(Metadata {
+            negated,
+            aspect: Some(self.8),
+            interacting_taxon: interacting_taxon(&self.12),
+            provided_by: self.14.clone(),
+            date: self.13.clone(),
+            properties: ListField::new(vec![])
+        })
+    }
+}
+
+impl HasExtensions<ParseError> for BaseGaf2_2Row {
+
+    fn extensions(&self, context: &Context) -> Result<Extensions, ParseError> {
+        gaf_extensions(&self.16, &self.15, context)
     }
 }
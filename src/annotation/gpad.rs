@@ -0,0 +1,521 @@
+//!
+//! GPAD (Gene Product Association Data) annotation rows.
+//!
+//! This follows the same pattern as GAF: a `RawGpad1_2Record` is what the CSV parser produces, which can be
+//! converted with `TryFrom` into a `BaseGpad1_2Row` made from the types in `fields`. The `Has*` traits from
+//! `model` are implemented for `BaseGpad1_2Row` so that it can be turned into a `GoAssociation` with `convert_raw`.
+//!
+//! `RawGpad2_0Record` and `BaseGpad2_0Row` are the GPAD 2.0 equivalents.
+//!
+//! GPAD only carries the identifier of the gene product. The rest of the `Subject` (label, name, synonyms, type, and
+//! taxon) is looked up in the `GeneProductTable` of the `Context`, loaded from a GPI file. If the gene product isn't
+//! found, these fields are left empty.
+//!
+
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::fmt;
+
+use super::fields;
+use super::fields::*;
+use super::error::{form, parse_column, parse_column_with, parse_optional_column, ParseError, ParseErrorKind, ParseErrors};
+use super::model::{self, HasSubject, HasRelation, HasTerm, HasEvidence, HasMetadata, HasExtensions,
+    Subject, Relation, Term, Evidence, Metadata, Extensions};
+use super::relation_qualifier;
+use crate::meta::Context;
+
+/// The `Subject` for `id` from the gene products in the `context`, or one with only the `id` if it isn't there.
+fn gene_product_subject(id: Curie, context: &Context) -> Subject {
+    match context.gene_products.get(&id) {
+        Some(product) => Subject::from(product),
+        None => Subject::new(id, NoSpaceString::new(""), None, ListField::new(vec![]), PlainString("".into()), None)
+    }
+}
+
+fn gene_product_taxon(id: &Curie, context: &Context) -> Option<Curie> {
+    context.gene_products.get(id).map(|product| product.taxon.clone())
+}
+
+///                           0       1       2       3       4       5       6       7               8       9       10      11
+#[derive(Debug, Clone, Deserialize, PartialEq, Serialize)]
+pub struct RawGpad1_2Record(String, String, String, String, String, String, String, Option<String>, String, String, String, String);
+
+impl fmt::Display for RawGpad1_2Record {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let taxon = self.7.as_deref().unwrap_or("");
+        let row: Vec<&str> = vec![&self.0, &self.1, &self.2, &self.3, &self.4, &self.5, &self.6, taxon, &self.8, &self.9, &self.10, &self.11];
+        write!(f, "{}", row.join("\t"))
+    }
+}
+
+///
+/// The Basic structure of a GPAD 1.2 row.
+///
+/// The Qualifier (2) must contain a relation label, optionally negated with `NOT`, and the evidence (5) is an ECO Curie.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BaseGpad1_2Row(
+    NoSpaceString,                              /// 0 DB
+    NoSpaceString,                              /// 1 DB Object ID
+    EitherOrBoth<Not, Label>,                   /// 2 Qualifier
+    Curie,                                      /// 3 GO ID
+    ListField<Curie>,                           /// 4 References
+    Curie,                                      /// 5 Evidence
+    ListField<Conjunction<Curie>>,              /// 6 With/From
+    Option<Curie>,                              /// 7 Interacting Taxon
+    fields::Date,                               /// 8 Date
+    NoSpaceString,                              /// 9 Assigned By
+    ListField<Conjunction<ClassExpression<Label, Curie>>>, /// 10 Annotation Extensions
+    ListField<Property>                         // 11 Annotation Properties
+);
+
+impl TryFrom<RawGpad1_2Record> for BaseGpad1_2Row {
+    type Error = ParseErrors;
+
+    fn try_from(gpad_record: RawGpad1_2Record) -> Result<BaseGpad1_2Row, ParseErrors> {
+        let mut errors = ParseErrors::new();
+        let f0 = errors.check(parse_column(1, "DB", gpad_record.0.as_str(), form::NO_SPACES));
+        let f1 = errors.check(parse_column(2, "DB Object ID", gpad_record.1.as_str(), form::NO_SPACES));
+        let f2 = errors.check(relation_qualifier(3, "Qualifier", gpad_record.2.as_str()));
+        let f3 = errors.check(parse_column(4, "GO ID", gpad_record.3.as_str(), form::CURIE));
+        let f4: Option<ListField<Curie>> = errors.check(parse_column(5, "DB:Reference", gpad_record.4.as_str(), form::CURIE_LIST));
+        let f5 = errors.check(parse_column(6, "Evidence Code", gpad_record.5.as_str(), form::CURIE));
+        let f6: Option<ListField<Conjunction<Curie>>> = errors.check(parse_column(7, "With (or) From", gpad_record.6.as_str(), form::WITH_FROM));
+        let f7 = errors.check(parse_optional_column(8, "Interacting Taxon ID", &gpad_record.7, form::CURIE));
+        let f8 = errors.check(parse_column_with(9, "Date", gpad_record.8.as_str(), form::DATE, Date::from_gaf));
+        let f9 = errors.check(parse_column(10, "Assigned By", gpad_record.9.as_str(), form::NO_SPACES));
+        let f10: Option<ListField<Conjunction<ClassExpression<Label, Curie>>>> = errors.check(parse_column(11, "Annotation Extension", gpad_record.10.as_str(), form::EXTENSIONS));
+        let f11: Option<ListField<Property>> = errors.check(parse_column(12, "Annotation Properties", gpad_record.11.as_str(), form::PROPERTIES));
+
+        match (f0, f1, f2, f3, f4, f5, f6, f7, f8, f9, f10, f11) {
+            (Some(f0), Some(f1), Some(f2), Some(f3), Some(f4), Some(f5), Some(f6), Some(f7), Some(f8), Some(f9), Some(f10), Some(f11)) =>
+                Ok(BaseGpad1_2Row(f0, f1, f2, f3, f4, f5, f6, f7, f8, f9, f10, f11)),
+            _ => Err(errors)
+        }
+    }
+}
+
+impl From<(model::GoAssociation, &Context)> for BaseGpad1_2Row {
+    /// GPAD 1.2 writes relations as labels, so this panics on relations without one, which `check_writable` rejects.
+    fn from((association, context): (model::GoAssociation, &Context)) -> BaseGpad1_2Row {
+        let relation = context.curie_to_label(&association.relation)
+            .expect("check_writable ensures the relation has a label");
+        let qualifier_field = if association.negated {
+            EitherOrBoth::Both(Not, relation)
+        } else {
+            EitherOrBoth::Right(relation)
+        };
+
+        let label_extensions = association.object_extension.map_new(|c| {
+            c.map_new(|cls| {
+                let ClassExpression {relation, filler} = cls;
+                let label = context.curie_to_label(relation)
+                    .expect("check_writable ensures extension relations have labels");
+                ClassExpression::new(label, filler.clone())
+            })
+        });
+
+        BaseGpad1_2Row(
+            NoSpaceString::new(association.subject.id.namespace),
+            NoSpaceString::new(association.subject.id.identifier),
+            qualifier_field,
+            association.object.id,
+            association.evidence.has_supporting_reference,
+            association.evidence.id,
+            association.evidence.with_support_from,
+            association.interacting_taxon,
+            association.date,
+            association.provided_by,
+            label_extensions,
+            association.properties
+        )
+    }
+}
+
+impl From<BaseGpad1_2Row> for RawGpad1_2Record {
+    fn from(base_row: BaseGpad1_2Row) -> RawGpad1_2Record {
+        RawGpad1_2Record(
+            base_row.0.value,
+            base_row.1.value,
+            match base_row.2 {
+                EitherOrBoth::Left(l) => l.string(),
+                EitherOrBoth::Right(r) => r.0,
+                EitherOrBoth::Both(l, r) => format!("{}|{}", l.string(), r.0)
+            },
+            base_row.3.string(),
+            base_row.4.to_string(),
+            base_row.5.string(),
+            base_row.6.to_string(),
+            base_row.7.map(|c| c.to_string()),
+            base_row.8.to_string(),
+            base_row.9.value,
+            base_row.10.to_string(),
+            base_row.11.to_string()
+        )
+    }
+}
+
+impl HasSubject<ParseError> for BaseGpad1_2Row {
+
+    fn subject(&self, context: &Context) -> Result<Subject, ParseError> {
+        Ok(gene_product_subject(Curie::new(&self.0.value, &self.1.value), context))
+    }
+}
+
+impl HasRelation<ParseError> for BaseGpad1_2Row {
+
+    fn relation(&self, context: &Context) -> Result<Relation, ParseError> {
+        let label = match &self.2 {
+            EitherOrBoth::Right(label) => label,
+            EitherOrBoth::Both(_, label) => label,
+            EitherOrBoth::Left(_) => return Err(ParseError::column(3, "Qualifier", "NOT", ParseErrorKind::Malformed(form::QUALIFIER)))
+        };
+
+        context.label_to_curie(label)
+            .ok_or_else(|| ParseError::column(3, "Qualifier", label.0.clone(), ParseErrorKind::Unknown("relation")))
+    }
+}
+
+impl HasTerm<ParseError> for BaseGpad1_2Row {
+
+    fn term(&self, context: &Context) -> Result<Term, ParseError> {
+        if self.3.same_namespace("GO") {
+            Ok(Term::new(self.3.clone(), gene_product_taxon(&Curie::new(&self.0.value, &self.1.value), context)))
+        } else {
+            Err(ParseError::column(4, "GO ID", self.3.to_string(), ParseErrorKind::Malformed(form::GO_TERM)))
+        }
+    }
+}
+
+impl HasEvidence<ParseError> for BaseGpad1_2Row {
+
+    fn evidence(&self, _: &Context) -> Result<Evidence, ParseError> {
+        if self.5.same_namespace("ECO") {
+            Ok(Evidence::new(self.5.clone(), self.4.clone(), self.6.clone()))
+        } else {
+            Err(ParseError::column(6, "Evidence Code", self.5.to_string(), ParseErrorKind::Malformed(form::ECO)))
+        }
+    }
+}
+
+impl HasMetadata<ParseError> for BaseGpad1_2Row {
+
+    fn metadata(&self, _: &Context) -> Result<Metadata, ParseError> {
+        let negated = match self.2 {
+            EitherOrBoth::Both(_, _) | EitherOrBoth::Left(_) => true,
+            EitherOrBoth::Right(_) => false
+        };
+
+        Ok(Metadata {
+            negated,
+            aspect: None,
+            interacting_taxon: self.7.clone(),
+            provided_by: self.9.clone(),
+            date: self.8.clone(),
+            properties: self.11.clone()
+        })
+    }
+}
+
+impl HasExtensions<ParseError> for BaseGpad1_2Row {
+
+    fn extensions(&self, context: &Context) -> Result<Extensions, ParseError> {
+        let object_extension = self.10.map_new_results(|conjunctions|
+            conjunctions.map_new_results(|ClassExpression { relation, filler }|
+                context.label_to_curie(relation)
+                    .ok_or_else(|| ParseError::column(11, "Annotation Extension", relation.0.clone(), ParseErrorKind::Unknown("relation")))
+                    .map(|curie_rel| ClassExpression::new(curie_rel, filler.clone()))));
+
+        object_extension.map(|obj_extension| Extensions::new(None, obj_extension))
+    }
+}
+
+///                           0       1               2       3       4       5       6       7               8       9       10      11
+#[derive(Debug, Clone, Deserialize, PartialEq, Serialize)]
+pub struct RawGpad2_0Record(String, Option<String>, String, String, String, String, String, Option<String>, String, String, String, String);
+
+impl fmt::Display for RawGpad2_0Record {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let negation = self.1.as_deref().unwrap_or("");
+        let taxon = self.7.as_deref().unwrap_or("");
+        let row: Vec<&str> = vec![&self.0, negation, &self.2, &self.3, &self.4, &self.5, &self.6, taxon, &self.8, &self.9, &self.10, &self.11];
+        write!(f, "{}", row.join("\t"))
+    }
+}
+
+///
+/// The Basic structure of a GPAD 2.0 row.
+///
+/// Unlike GPAD 1.2, the subject (0) is a single Curie, negation (1) has its own column, and the relation (2)
+/// and annotation extension relations (10) are Curies rather than labels. Dates (8) are `YYYY-MM-DD`, with
+/// an optional `THH:MM:SS` time.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BaseGpad2_0Row(
+    Curie,                                      /// 0 DB:DB Object ID
+    Option<Not>,                                /// 1 Negation
+    Curie,                                      /// 2 Relation
+    Curie,                                      /// 3 Ontology Class ID
+    ListField<Curie>,                           /// 4 References
+    Curie,                                      /// 5 Evidence
+    ListField<Conjunction<Curie>>,              /// 6 With/From
+    Option<Curie>,                              /// 7 Interacting Taxon
+    fields::Date,                               /// 8 Date
+    NoSpaceString,                              /// 9 Assigned By
+    ListField<Conjunction<ClassExpression<Curie, Curie>>>, /// 10 Annotation Extensions
+    ListField<Property>                         // 11 Annotation Properties
+);
+
+impl TryFrom<RawGpad2_0Record> for BaseGpad2_0Row {
+    type Error = ParseErrors;
+
+    fn try_from(gpad_record: RawGpad2_0Record) -> Result<BaseGpad2_0Row, ParseErrors> {
+        let mut errors = ParseErrors::new();
+        let f0 = errors.check(parse_column(1, "DB:DB Object ID", gpad_record.0.as_str(), form::CURIE));
+        let f1 = errors.check(parse_optional_column(2, "Negation", &gpad_record.1, form::NOT));
+        let f2 = errors.check(parse_column(3, "Relation", gpad_record.2.as_str(), form::CURIE));
+        let f3 = errors.check(parse_column(4, "Ontology Class ID", gpad_record.3.as_str(), form::CURIE));
+        let f4: Option<ListField<Curie>> = errors.check(parse_column(5, "Reference", gpad_record.4.as_str(), form::CURIE_LIST));
+        let f5 = errors.check(parse_column(6, "Evidence Type", gpad_record.5.as_str(), form::CURIE));
+        let f6: Option<ListField<Conjunction<Curie>>> = errors.check(parse_column(7, "With (or) From", gpad_record.6.as_str(), form::WITH_FROM));
+        let f7 = errors.check(parse_optional_column(8, "Interacting Taxon ID", &gpad_record.7, form::CURIE));
+        let f8 = errors.check(parse_column_with(9, "Date", gpad_record.8.as_str(), form::ISO_DATE, Date::from_iso));
+        let f9 = errors.check(parse_column(10, "Assigned By", gpad_record.9.as_str(), form::NO_SPACES));
+        let f10: Option<ListField<Conjunction<ClassExpression<Curie, Curie>>>> = errors.check(parse_column(11, "Annotation Extensions", gpad_record.10.as_str(), form::EXTENSIONS));
+        let f11: Option<ListField<Property>> = errors.check(parse_column(12, "Annotation Properties", gpad_record.11.as_str(), form::PROPERTIES));
+
+        match (f0, f1, f2, f3, f4, f5, f6, f7, f8, f9, f10, f11) {
+            (Some(f0), Some(f1), Some(f2), Some(f3), Some(f4), Some(f5), Some(f6), Some(f7), Some(f8), Some(f9), Some(f10), Some(f11)) =>
+                Ok(BaseGpad2_0Row(f0, f1, f2, f3, f4, f5, f6, f7, f8, f9, f10, f11)),
+            _ => Err(errors)
+        }
+    }
+}
+
+/// GPAD 2.0 writes taxa as `NCBITaxon:1234`, while the rest of the model uses the GAF `taxon:1234`.
+fn from_ncbi_taxon(taxon: &Curie) -> Curie {
+    if taxon.same_namespace("NCBITaxon") {
+        Curie::new("taxon", &taxon.identifier)
+    } else {
+        taxon.clone()
+    }
+}
+
+fn to_ncbi_taxon(taxon: &Curie) -> Curie {
+    if taxon.same_namespace("taxon") {
+        Curie::new("NCBITaxon", &taxon.identifier)
+    } else {
+        taxon.clone()
+    }
+}
+
+impl From<(model::GoAssociation, &Context)> for BaseGpad2_0Row {
+    fn from((association, _): (model::GoAssociation, &Context)) -> BaseGpad2_0Row {
+        let negation = if association.negated { Some(Not) } else { None };
+
+        BaseGpad2_0Row(
+            association.subject.id,
+            negation,
+            association.relation,
+            association.object.id,
+            association.evidence.has_supporting_reference,
+            association.evidence.id,
+            association.evidence.with_support_from,
+            association.interacting_taxon.as_ref().map(to_ncbi_taxon),
+            association.date,
+            association.provided_by,
+            association.object_extension,
+            association.properties
+        )
+    }
+}
+
+impl From<BaseGpad2_0Row> for RawGpad2_0Record {
+    fn from(base_row: BaseGpad2_0Row) -> RawGpad2_0Record {
+        RawGpad2_0Record(
+            base_row.0.string(),
+            base_row.1.map(|n| n.string()),
+            base_row.2.string(),
+            base_row.3.string(),
+            base_row.4.to_string(),
+            base_row.5.string(),
+            base_row.6.to_string(),
+            base_row.7.map(|c| c.to_string()),
+            base_row.8.iso_string(),
+            base_row.9.value,
+            base_row.10.to_string(),
+            base_row.11.to_string()
+        )
+    }
+}
+
+impl HasSubject<ParseError> for BaseGpad2_0Row {
+
+    fn subject(&self, context: &Context) -> Result<Subject, ParseError> {
+        Ok(gene_product_subject(self.0.clone(), context))
+    }
+}
+
+impl HasRelation<ParseError> for BaseGpad2_0Row {
+
+    fn relation(&self, _: &Context) -> Result<Relation, ParseError> {
+        Ok(self.2.clone())
+    }
+}
+
+impl HasTerm<ParseError> for BaseGpad2_0Row {
+
+    fn term(&self, context: &Context) -> Result<Term, ParseError> {
+        if self.3.same_namespace("GO") {
+            Ok(Term::new(self.3.clone(), gene_product_taxon(&self.0, context)))
+        } else {
+            Err(ParseError::column(4, "Ontology Class ID", self.3.to_string(), ParseErrorKind::Malformed(form::GO_TERM)))
+        }
+    }
+}
+
+impl HasEvidence<ParseError> for BaseGpad2_0Row {
+
+    fn evidence(&self, _: &Context) -> Result<Evidence, ParseError> {
+        if self.5.same_namespace("ECO") {
+            Ok(Evidence::new(self.5.clone(), self.4.clone(), self.6.clone()))
+        } else {
+            Err(ParseError::column(6, "Evidence Type", self.5.to_string(), ParseErrorKind::Malformed(form::ECO)))
+        }
+    }
+}
+
+impl HasMetadata<ParseError> for BaseGpad2_0Row {
+
+    fn metadata(&self, _: &Context) -> Result<Metadata, ParseError> {
+        Ok(Metadata {
+            negated: self.1.is_some(),
+            aspect: None,
+            interacting_taxon: self.7.as_ref().map(from_ncbi_taxon),
+            provided_by: self.9.clone(),
+            date: self.8.clone(),
+            properties: self.11.clone()
+        })
+    }
+}
+
+impl HasExtensions<ParseError> for BaseGpad2_0Row {
+
+    fn extensions(&self, _: &Context) -> Result<Extensions, ParseError> {
+        Ok(Extensions::new(None, self.10.clone()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::annotation::{RawGaf2_1Record, BaseGaf2_1Row};
+    use crate::annotation::gpi::GeneProduct;
+    use csv::ReaderBuilder;
+    use serde::de::DeserializeOwned;
+
+    /// Reads a single GPAD `line` as the raw record `T` of its version.
+    fn read_gpad<T: DeserializeOwned>(line: &str) -> T {
+        let mut gpad_reader = ReaderBuilder::new()
+            .delimiter(b'\t')
+            .flexible(true)
+            .has_headers(false)
+            .comment(Some(b'!'))
+            .from_reader(line.as_bytes());
+
+        gpad_reader.deserialize().next().unwrap().unwrap()
+    }
+
+    #[test]
+    fn test_convert_gpad_1_2_to_association() {
+        let example = "MGI\tMGI:98961\tNOT|involved_in\tGO:0099175\tPMID:21670302\tECO:0000315\tMGI:MGI:1|MGI:MGI:2,MGI:MGI:3\ttaxon:10090\t20180711\tSynGO\toccurs_in(GO:0098978)\tcontributor=https://orcid.org/0000-0002-1706-4196";
+        let raw = read_gpad(example);
+        let context = Context::default();
+        let association = model::convert_raw::<RawGpad1_2Record, BaseGpad1_2Row>(raw, &context).unwrap();
+
+        assert_eq!(association.subject.id, Curie::new("MGI", "MGI:98961"));
+        assert_eq!(association.relation, Curie::new("RO", "0002331"));
+        assert!(association.negated);
+        assert_eq!(association.evidence.id, Curie::new("ECO", "0000315"));
+        assert_eq!(association.evidence.with_support_from, ListField::new(vec![
+            Conjunction::new(vec![Curie::new("MGI", "MGI:1")]),
+            Conjunction::new(vec![Curie::new("MGI", "MGI:2"), Curie::new("MGI", "MGI:3")])
+        ]));
+        assert_eq!(association.interacting_taxon, Some(Curie::new("taxon", "10090")));
+        assert_eq!(association.object_extension, ListField::new(vec![
+            Conjunction::new(vec![ClassExpression::new(Curie::new("BFO", "0000066"), Curie::new("GO", "0098978"))])
+        ]));
+        assert_eq!(association.properties, ListField::new(vec![Property("contributor".into(), "https://orcid.org/0000-0002-1706-4196".into())]));
+    }
+
+    #[test]
+    fn test_gpad_1_2_round_trip() {
+        let example = "MGI\tMGI:98961\tenables\tGO:0003674\tPMID:21670302\tECO:0000314\t\t\t20180711\tSynGO\t\t";
+        let context = Context::default();
+        let association = model::convert_raw::<RawGpad1_2Record, BaseGpad1_2Row>(read_gpad(example), &context).unwrap();
+
+        let written: RawGpad1_2Record = BaseGpad1_2Row::from((association, &context)).into();
+        assert_eq!(written.to_string(), example);
+    }
+
+    #[test]
+    fn test_gaf_to_gpad_1_2() {
+        let gaf = RawGaf2_1Record(
+            "MGI".into(), "MGI:98961".into(), "Wnt7a".into(), None, "GO:0099175".into(), "MGI:MGI:5014434|PMID:21670302".into(),
+            "IMP".into(), "".into(), 'P', None, "".into(), "protein".into(), "taxon:10090".into(), "20180711".into(),
+            "SynGO".into(), "occurs_in(GO:0098978),occurs_in(EMAPA:35405)".into(), None);
+        let context = Context::default();
+        let association = model::convert_raw::<RawGaf2_1Record, BaseGaf2_1Row>(gaf, &context).unwrap();
+
+        let gpad: RawGpad1_2Record = BaseGpad1_2Row::from((association, &context)).into();
+        assert_eq!(gpad.to_string(), "MGI\tMGI:98961\tinvolved_in\tGO:0099175\tMGI:MGI:5014434|PMID:21670302\tECO:0000315\t\t\t20180711\tSynGO\toccurs_in(GO:0098978),occurs_in(EMAPA:35405)\t");
+    }
+
+    #[test]
+    fn test_convert_gpad_2_0_to_association() {
+        let example = "MGI:MGI:98961\tNOT\tRO:0002331\tGO:0099175\tPMID:21670302\tECO:0000315\t\tNCBITaxon:10090\t2018-07-11T13:04:59\tSynGO\tBFO:0000066(GO:0098978)\t";
+        let context = Context::default();
+        let association = model::convert_raw::<RawGpad2_0Record, BaseGpad2_0Row>(read_gpad(example), &context).unwrap();
+
+        assert_eq!(association.subject.id, Curie::new("MGI", "MGI:98961"));
+        assert!(association.negated);
+        assert_eq!(association.relation, Curie::new("RO", "0002331"));
+        assert_eq!(association.interacting_taxon, Some(Curie::new("taxon", "10090")));
+        assert_eq!(association.date.iso_string(), "2018-07-11T13:04:59");
+        assert_eq!(association.object_extension, ListField::new(vec![
+            Conjunction::new(vec![ClassExpression::new(Curie::new("BFO", "0000066"), Curie::new("GO", "0098978"))])
+        ]));
+    }
+
+    #[test]
+    fn test_gpad_2_0_round_trip() {
+        let context = Context::default();
+        for example in &[
+            "MGI:MGI:98961\tNOT\tRO:0002331\tGO:0099175\tPMID:21670302\tECO:0000315\tMGI:MGI:1,MGI:MGI:2\tNCBITaxon:10090\t2018-07-11T13:04:59\tSynGO\tBFO:0000066(GO:0098978)\tcontributor=https://orcid.org/0000-0002-1706-4196",
+            "UniProtKB:P12345\t\tRO:0002327\tGO:0003674\tGO_REF:0000015\tECO:0000307\t\t\t2018-07-11\tUniProt\t\t"
+        ] {
+            let association = model::convert_raw::<RawGpad2_0Record, BaseGpad2_0Row>(read_gpad(example), &context).unwrap();
+            let written: RawGpad2_0Record = BaseGpad2_0Row::from((association, &context)).into();
+            assert_eq!(&written.to_string(), example);
+        }
+    }
+
+    #[test]
+    fn test_gpad_subject_from_gene_products() {
+        let product = GeneProduct {
+            id: Curie::new("MGI", "MGI:98961"),
+            symbol: NoSpaceString::new("Wnt7a"),
+            name: Some(PlainString("wingless-type MMTV integration site family, member 7A".into())),
+            synonyms: ListField::new(vec![PlainString("tw".into())]),
+            kind: PlainString("protein".into()),
+            taxon: Curie::new("taxon", "10090"),
+            parent: None,
+            xrefs: ListField::new(vec![])
+        };
+        let context = Context::default().add_gene_products(vec![product].into_iter().collect());
+        let example = "MGI\tMGI:98961\tinvolved_in\tGO:0099175\tPMID:21670302\tECO:0000315\t\t\t20180711\tSynGO\t\t";
+        let association = model::convert_raw::<RawGpad1_2Record, BaseGpad1_2Row>(read_gpad(example), &context).unwrap();
+
+        assert_eq!(association.subject.label, NoSpaceString::new("Wnt7a"));
+        assert_eq!(association.subject.taxon, Some(Curie::new("taxon", "10090")));
+        assert_eq!(association.object.taxon, Some(Curie::new("taxon", "10090")));
+    }
+}
@@ -0,0 +1,289 @@
+//!
+//! GPI (Gene Product Information) rows.
+//!
+//! A GPI file describes the gene products that are referenced by identifier in a GPAD file. Each line of a
+//! GPI 1.2 file (`RawGpi1_2Record`) or GPI 2.0 file (`RawGpi2_0Record`) is converted into a `GeneProduct`,
+//! and these are collected into a `GeneProductTable` indexed by the gene product `Curie`.
+//!
+//! Going the other way, a `GeneProduct` can be made from the `Subject` of an annotation and written as either GPI
+//! version, which is how a GPI is produced alongside a GPAD converted from a GAF.
+//!
+//! A `GeneProductTable` can be attached to a `Context` so that GPAD annotations can fill in the `Subject`
+//! fields (label, fullname, synonyms, kind, taxon) that GPAD does not carry.
+//!
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt;
+use std::iter::FromIterator;
+
+use super::fields::*;
+use super::error::{form, parse_column, parse_optional_column, ParseError, ParseErrors};
+use super::model::Subject;
+
+///                          0       1       2       3               4       5       6       7               8       9
+#[derive(Debug, Clone, Deserialize, PartialEq, Serialize)]
+pub struct RawGpi1_2Record(String, String, String, Option<String>, String, String, String, Option<String>, String, String);
+
+impl fmt::Display for RawGpi1_2Record {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = self.3.as_deref().unwrap_or("");
+        let parent = self.7.as_deref().unwrap_or("");
+        let row: Vec<&str> = vec![&self.0, &self.1, &self.2, name, &self.4, &self.5, &self.6, parent, &self.8, &self.9];
+        write!(f, "{}", row.join("\t"))
+    }
+}
+
+///                          0       1       2               3       4       5       6       7       8       9       10
+#[derive(Debug, Clone, Deserialize, PartialEq, Serialize)]
+pub struct RawGpi2_0Record(String, String, Option<String>, String, String, String, String, String, String, String, String);
+
+impl fmt::Display for RawGpi2_0Record {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = self.2.as_deref().unwrap_or("");
+        let row: Vec<&str> = vec![&self.0, &self.1, name, &self.3, &self.4, &self.5, &self.6, &self.7, &self.8, &self.9, &self.10];
+        write!(f, "{}", row.join("\t"))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GeneProduct {
+    pub id: Curie,
+    pub symbol: NoSpaceString,
+    pub name: Option<PlainString>,
+    pub synonyms: ListField<PlainString>,
+    pub kind: PlainString,
+    pub taxon: Curie,
+    pub parent: Option<Curie>,
+    pub xrefs: ListField<Curie>
+}
+
+impl From<&GeneProduct> for Subject {
+    fn from(product: &GeneProduct) -> Subject {
+        Subject::new(
+            product.id.clone(),
+            product.symbol.clone(),
+            product.name.clone(),
+            product.synonyms.clone(),
+            product.kind.clone(),
+            Some(product.taxon.clone()))
+    }
+}
+
+impl TryFrom<&Subject> for GeneProduct {
+    type Error = ParseError;
+
+    /// GPI lines require a taxon, so this is an Err if the `subject` has none.
+    fn try_from(subject: &Subject) -> Result<GeneProduct, ParseError> {
+        let taxon = subject.taxon.clone().ok_or_else(|| ParseError::Line(format!("No taxon for gene product `{}`", subject.id)))?;
+
+        Ok(GeneProduct {
+            id: subject.id.clone(),
+            symbol: subject.label.clone(),
+            name: subject.fullname.clone(),
+            synonyms: subject.synonyms.clone(),
+            kind: subject.kind.clone(),
+            taxon,
+            parent: None,
+            xrefs: ListField::new(vec![])
+        })
+    }
+}
+
+/// GPI 2.0 writes taxa as `NCBITaxon:1234`, while the rest of the model uses the GAF `taxon:1234`.
+fn from_ncbi_taxon(taxon: Curie) -> Curie {
+    if taxon.same_namespace("NCBITaxon") {
+        Curie::new(String::from("taxon"), taxon.identifier)
+    } else {
+        taxon
+    }
+}
+
+fn to_ncbi_taxon(taxon: Curie) -> Curie {
+    if taxon.same_namespace("taxon") {
+        Curie::new(String::from("NCBITaxon"), taxon.identifier)
+    } else {
+        taxon
+    }
+}
+
+impl TryFrom<RawGpi1_2Record> for GeneProduct {
+    type Error = ParseErrors;
+
+    fn try_from(gpi_record: RawGpi1_2Record) -> Result<GeneProduct, ParseErrors> {
+        let mut errors = ParseErrors::new();
+        let id = errors.check(parse_column(2, "DB Object ID", format!("{}:{}", gpi_record.0, gpi_record.1).as_str(), form::CURIE));
+        let symbol = errors.check(parse_column(3, "DB Object Symbol", gpi_record.2.as_str(), form::NO_SPACES));
+        let name = errors.check(parse_optional_column(4, "DB Object Name", &gpi_record.3, form::TEXT));
+        let synonyms = errors.check(parse_column(5, "DB Object Synonym(s)", gpi_record.4.as_str(), form::TEXT_LIST));
+        let kind = errors.check(parse_column(6, "DB Object Type", gpi_record.5.as_str(), form::TEXT));
+        let taxon = errors.check(parse_column(7, "Taxon", gpi_record.6.as_str(), form::CURIE));
+        let parent = errors.check(parse_optional_column(8, "Parent Object ID", &gpi_record.7, form::CURIE));
+        let xrefs = errors.check(parse_column(9, "DB Xref(s)", gpi_record.8.as_str(), form::CURIE_LIST));
+
+        match (id, symbol, name, synonyms, kind, taxon, parent, xrefs) {
+            (Some(id), Some(symbol), Some(name), Some(synonyms), Some(kind), Some(taxon), Some(parent), Some(xrefs)) =>
+                Ok(GeneProduct { id, symbol, name, synonyms, kind, taxon: from_ncbi_taxon(taxon), parent, xrefs }),
+            _ => Err(errors)
+        }
+    }
+}
+
+impl TryFrom<RawGpi2_0Record> for GeneProduct {
+    type Error = ParseErrors;
+
+    /// GPI 2.0 allows several parent proteins, but only the first is kept as the `parent`.
+    fn try_from(gpi_record: RawGpi2_0Record) -> Result<GeneProduct, ParseErrors> {
+        let mut errors = ParseErrors::new();
+        let id = errors.check(parse_column(1, "DB:DB Object ID", gpi_record.0.as_str(), form::CURIE));
+        let symbol = errors.check(parse_column(2, "DB Object Symbol", gpi_record.1.as_str(), form::NO_SPACES));
+        let name = errors.check(parse_optional_column(3, "DB Object Name", &gpi_record.2, form::TEXT));
+        let synonyms = errors.check(parse_column(4, "DB Object Synonyms", gpi_record.3.as_str(), form::TEXT_LIST));
+        let kind = errors.check(parse_column(5, "DB Object Type", gpi_record.4.as_str(), form::CURIE_LIST));
+        let taxon = errors.check(parse_column(6, "DB Object Taxon", gpi_record.5.as_str(), form::CURIE));
+        let parents: Option<ListField<Curie>> = errors.check(parse_column(8, "Parent Protein", gpi_record.7.as_str(), form::CURIE_LIST));
+        let xrefs = errors.check(parse_column(10, "DB Xrefs", gpi_record.9.as_str(), form::CURIE_LIST));
+
+        match (id, symbol, name, synonyms, kind, taxon, parents, xrefs) {
+            (Some(id), Some(symbol), Some(name), Some(synonyms), Some(kind), Some(taxon), Some(parents), Some(xrefs)) =>
+                Ok(GeneProduct { id, symbol, name, synonyms, kind, taxon: from_ncbi_taxon(taxon), parent: parents.items().first().cloned(), xrefs }),
+            _ => Err(errors)
+        }
+    }
+}
+
+impl From<GeneProduct> for RawGpi1_2Record {
+    fn from(product: GeneProduct) -> RawGpi1_2Record {
+        RawGpi1_2Record(
+            product.id.namespace,
+            product.id.identifier,
+            product.symbol.value,
+            product.name.map(|n| n.0),
+            product.synonyms.to_string(),
+            product.kind.0,
+            product.taxon.to_string(),
+            product.parent.map(|p| p.to_string()),
+            product.xrefs.to_string(),
+            String::new()
+        )
+    }
+}
+
+impl From<GeneProduct> for RawGpi2_0Record {
+    fn from(product: GeneProduct) -> RawGpi2_0Record {
+        RawGpi2_0Record(
+            product.id.to_string(),
+            product.symbol.value,
+            product.name.map(|n| n.0),
+            product.synonyms.to_string(),
+            product.kind.0,
+            to_ncbi_taxon(product.taxon).to_string(),
+            String::new(),
+            product.parent.map(|p| p.to_string()).unwrap_or_default(),
+            String::new(),
+            product.xrefs.to_string(),
+            String::new()
+        )
+    }
+}
+
+/// All the `GeneProduct`s from a GPI file, looked up by their `Curie`.
+#[derive(Debug, Default)]
+pub struct GeneProductTable {
+    products: HashMap<Curie, GeneProduct>
+}
+
+impl GeneProductTable {
+    pub fn new() -> GeneProductTable {
+        GeneProductTable {
+            products: HashMap::new()
+        }
+    }
+
+    pub fn insert(&mut self, product: GeneProduct) {
+        self.products.insert(product.id.clone(), product);
+    }
+
+    pub fn get(&self, id: &Curie) -> Option<&GeneProduct> {
+        self.products.get(id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.products.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.products.is_empty()
+    }
+}
+
+impl FromIterator<GeneProduct> for GeneProductTable {
+    fn from_iter<I: IntoIterator<Item=GeneProduct>>(iter: I) -> GeneProductTable {
+        let mut table = GeneProductTable::new();
+        for product in iter {
+            table.insert(product);
+        }
+        table
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use csv::ReaderBuilder;
+
+    fn reader(line: &str) -> csv::Reader<&[u8]> {
+        ReaderBuilder::new()
+            .delimiter(b'\t')
+            .flexible(true)
+            .has_headers(false)
+            .comment(Some(b'!'))
+            .from_reader(line.as_bytes())
+    }
+
+    #[test]
+    fn test_gpi_1_2_to_gene_product() {
+        let example = "MGI\tMGI:98961\tWnt7a\twingless-type MMTV integration site family, member 7A\ttw|Wnt-7a\tprotein\ttaxon:10090\t\tUniProtKB:P24383\t";
+        let raw: RawGpi1_2Record = reader(example).deserialize().next().unwrap().unwrap();
+        let product = GeneProduct::try_from(raw).unwrap();
+
+        assert_eq!(product.id, Curie::new("MGI", "MGI:98961"));
+        assert_eq!(product.symbol, NoSpaceString::new("Wnt7a"));
+        assert_eq!(product.synonyms, ListField::new(vec![PlainString("tw".into()), PlainString("Wnt-7a".into())]));
+        assert_eq!(product.taxon, Curie::new("taxon", "10090"));
+        assert_eq!(product.parent, None);
+        assert_eq!(product.xrefs, ListField::new(vec![Curie::new("UniProtKB", "P24383")]));
+    }
+
+    #[test]
+    fn test_gpi_2_0_to_gene_product() {
+        let example = "UniProtKB:P24383-1\tWnt7a\tProtein Wnt-7a\t\tPR:000000001\tNCBITaxon:10090\t\tUniProtKB:P24383\t\t\t";
+        let raw: RawGpi2_0Record = reader(example).deserialize().next().unwrap().unwrap();
+        let product = GeneProduct::try_from(raw).unwrap();
+
+        assert_eq!(product.id, Curie::new("UniProtKB", "P24383-1"));
+        assert_eq!(product.kind, PlainString("PR:000000001".into()));
+        assert_eq!(product.taxon, Curie::new("taxon", "10090"));
+        assert_eq!(product.parent, Some(Curie::new("UniProtKB", "P24383")));
+
+        let table: GeneProductTable = vec![product].into_iter().collect();
+        let subject = Subject::from(table.get(&Curie::new("UniProtKB", "P24383-1")).unwrap());
+        assert_eq!(subject.label, NoSpaceString::new("Wnt7a"));
+        assert_eq!(subject.taxon, Some(Curie::new("taxon", "10090")));
+    }
+
+    #[test]
+    fn test_subject_to_gpi() {
+        let subject = Subject::new(Curie::new("MGI", "MGI:98961"), NoSpaceString::new("Wnt7a"), None,
+            ListField::new(vec![PlainString("tw".into())]), PlainString("protein".into()), Some(Curie::new("taxon", "10090")));
+        let product = GeneProduct::try_from(&subject).unwrap();
+
+        assert_eq!(RawGpi1_2Record::from(product.clone()).to_string(), "MGI\tMGI:98961\tWnt7a\t\ttw\tprotein\ttaxon:10090\t\t\t");
+        assert_eq!(RawGpi2_0Record::from(product.clone()).to_string(), "MGI:MGI:98961\tWnt7a\t\ttw\tprotein\tNCBITaxon:10090\t\t\t\t\t");
+        assert_eq!(GeneProduct::try_from(RawGpi2_0Record::from(product.clone())), Ok(product));
+
+        let no_taxon = Subject { taxon: None, ..subject };
+        assert!(GeneProduct::try_from(&no_taxon).is_err());
+    }
+}
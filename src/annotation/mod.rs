@@ -4,38 +4,129 @@ use std::convert::TryFrom;
 use std::fmt;
 
 pub mod fields;
+pub mod error;
 pub mod model;
 pub mod gaf;
+pub mod gpad;
+pub mod gpi;
 
 use crate::meta::Context;
 use crate::ontology::NodeAspect;
 
 use fields::*;
+use error::{form, parse_column, parse_column_with, parse_optional_column, ParseError, ParseErrorKind, ParseErrors};
+use gpad::{RawGpad1_2Record, BaseGpad1_2Row, RawGpad2_0Record, BaseGpad2_0Row};
+use gpi::{RawGpi1_2Record, RawGpi2_0Record};
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub enum GafVersion {
     Gaf2_1,
     Gaf2_2
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub enum GpadVersion {
     Gpad1_2,
     Gpad2_0
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub enum GpiVersion {
-    Gpi1_2
+    Gpi1_2,
+    Gpi2_0
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub enum DocumentType {
     Gaf(GafVersion),
     Gpad(GpadVersion),
     Gpi(GpiVersion)
 }
 
+impl DocumentType {
+    /// Reads the document type from a version header comment, like `gaf-version: 2.2`.
+    /// Returns None if the comment is not a version comment, or if the version is not one we know about.
+    pub fn from_version_comment(comment: &str) -> Option<DocumentType> {
+        let (key, value) = comment.split_once(':')?;
+        match (key.trim(), value.trim()) {
+            ("gaf-version", "2.1") => Some(DocumentType::Gaf(GafVersion::Gaf2_1)),
+            ("gaf-version", "2.2") => Some(DocumentType::Gaf(GafVersion::Gaf2_2)),
+            ("gpa-version", "1.2") | ("gpad-version", "1.2") => Some(DocumentType::Gpad(GpadVersion::Gpad1_2)),
+            ("gpad-version", "2.0") => Some(DocumentType::Gpad(GpadVersion::Gpad2_0)),
+            ("gpi-version", "1.2") => Some(DocumentType::Gpi(GpiVersion::Gpi1_2)),
+            ("gpi-version", "2.0") => Some(DocumentType::Gpi(GpiVersion::Gpi2_0)),
+            _ => None
+        }
+    }
+
+    /// The version header comment for this document type, without the leading `!`
+    pub fn version_comment(&self) -> String {
+        match self {
+            DocumentType::Gaf(GafVersion::Gaf2_1) => "gaf-version: 2.1",
+            DocumentType::Gaf(GafVersion::Gaf2_2) => "gaf-version: 2.2",
+            DocumentType::Gpad(GpadVersion::Gpad1_2) => "gpa-version: 1.2",
+            DocumentType::Gpad(GpadVersion::Gpad2_0) => "gpad-version: 2.0",
+            DocumentType::Gpi(GpiVersion::Gpi1_2) => "gpi-version: 1.2",
+            DocumentType::Gpi(GpiVersion::Gpi2_0) => "gpi-version: 2.0"
+        }.into()
+    }
+}
+
+impl DocumentType {
+    /// The number of tab separated columns in a line of this document type.
+    pub fn column_count(&self) -> usize {
+        match self {
+            DocumentType::Gaf(_) => 17,
+            DocumentType::Gpad(_) => 12,
+            DocumentType::Gpi(GpiVersion::Gpi1_2) => 10,
+            DocumentType::Gpi(GpiVersion::Gpi2_0) => 11
+        }
+    }
+
+    /// The number of columns up to the last required one. Lines may leave off the optional columns after it, like the
+    /// Annotation Extension and Gene Product Form ID of GAF.
+    pub fn min_column_count(&self) -> usize {
+        match self {
+            DocumentType::Gaf(_) => 15,
+            DocumentType::Gpad(_) => 10,
+            DocumentType::Gpi(_) => 9
+        }
+    }
+
+    ///
+    /// Guesses the document type from the columns of an annotation line, for files without a version header.
+    /// 
+    /// GAF 2.2 is distinguished from GAF 2.1 by a relation in the qualifier that GAF 2.1 doesn't allow, and GPAD 2.0
+    /// from GPAD 1.2 by its `NOT` or empty negation column.
+    pub fn from_columns(columns: &[&str]) -> Option<DocumentType> {
+        match columns.len() {
+            15..=17 => {
+                if columns[3].split('|').all(|q| q.is_empty() || q == "NOT" || GAF_2_1_RELATIONS.contains(&q)) {
+                    Some(DocumentType::Gaf(GafVersion::Gaf2_1))
+                } else {
+                    Some(DocumentType::Gaf(GafVersion::Gaf2_2))
+                }
+            },
+            12 => {
+                if columns[0].contains(':') && (columns[1].is_empty() || columns[1] == "NOT") {
+                    Some(DocumentType::Gpad(GpadVersion::Gpad2_0))
+                } else {
+                    Some(DocumentType::Gpad(GpadVersion::Gpad1_2))
+                }
+            },
+            11 => Some(DocumentType::Gpi(GpiVersion::Gpi2_0)),
+            10 => Some(DocumentType::Gpi(GpiVersion::Gpi1_2)),
+            _ => None
+        }
+    }
+}
+
+/// Header comment keys that are replaced, rather than copied, when writing a new header.
+const GENERATED_HEADER_KEYS: [&str; 6] = ["gaf-version", "gpa-version", "gpad-version", "gpi-version", "generated-by", "date-generated"];
+
+///
+/// An annotation file: the `DocumentType`, the `!` comments from the header (without the leading `!`),
+/// and any annotations.
 #[derive(Debug, Clone)]
 pub struct AnnotationDocument<A> {
     document_type: DocumentType,
@@ -43,6 +134,201 @@ pub struct AnnotationDocument<A> {
     annotations: Vec<A>
 }
 
+impl<A> AnnotationDocument<A> {
+    pub fn new(document_type: DocumentType, comments: Vec<String>) -> AnnotationDocument<A> {
+        AnnotationDocument {
+            document_type,
+            comments,
+            annotations: vec![]
+        }
+    }
+
+    /// Makes an `AnnotationDocument` from header comments, using the version comment to find the `DocumentType`.
+    /// Returns None if there is no version comment that we recognize.
+    pub fn from_header(comments: Vec<String>) -> Option<AnnotationDocument<A>> {
+        let document_type = comments.iter().find_map(|c| DocumentType::from_version_comment(c))?;
+        Some(AnnotationDocument::new(document_type, comments))
+    }
+
+    pub fn document_type(&self) -> DocumentType {
+        self.document_type
+    }
+
+    pub fn comments(&self) -> &[String] {
+        self.comments.as_slice()
+    }
+
+    pub fn annotations(&self) -> &[A] {
+        self.annotations.as_slice()
+    }
+
+    pub fn add_annotation(&mut self, annotation: A) {
+        self.annotations.push(annotation);
+    }
+
+    ///
+    /// The header lines to write out for this document, including the leading `!`. This starts with the version,
+    /// `generated-by`, and `date-generated` comments for this document, followed by the remaining original comments.
+    pub fn header(&self) -> Vec<String> {
+        let mut header = vec![
+            format!("!{}", self.document_type.version_comment()),
+            "!generated-by: fast-go-annotations".to_string(),
+            format!("!date-generated: {}", chrono::Utc::now().format("%Y-%m-%d"))
+        ];
+
+        let original = self.comments.iter()
+            .filter(|comment| {
+                let key = comment.split_once(':').map(|(k, _)| k.trim());
+                !matches!(key, Some(k) if GENERATED_HEADER_KEYS.contains(&k))
+            })
+            .map(|comment| format!("!{}", comment));
+
+        header.extend(original);
+        header
+    }
+}
+
+///
+/// A raw line of any of the supported annotation formats, as parsed by CSV.
+/// 
+/// This serializes as the wrapped record, so it can be written with a CSV writer.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum RawRecord {
+    Gaf2_1(RawGaf2_1Record),
+    Gaf2_2(RawGaf2_2Record),
+    Gpad1_2(RawGpad1_2Record),
+    Gpad2_0(RawGpad2_0Record),
+    Gpi1_2(RawGpi1_2Record),
+    Gpi2_0(RawGpi2_0Record)
+}
+
+impl RawRecord {
+    ///
+    /// Deserializes a CSV record as a line of `document_type`. The line may leave off optional columns at the end, down
+    /// to the document type's `min_column_count`, and they are read as empty.
+    pub fn from_record(record: &csv::StringRecord, document_type: DocumentType) -> Result<RawRecord, ParseError> {
+        let (min, max) = (document_type.min_column_count(), document_type.column_count());
+        if record.len() < min || record.len() > max {
+            return Err(ParseError::ColumnCount { min, max, found: record.len() });
+        }
+        let mut padded;
+        let record = if record.len() < max {
+            padded = record.clone();
+            while padded.len() < max {
+                padded.push_field("");
+            }
+            &padded
+        } else {
+            record
+        };
+
+        let raw = match document_type {
+            DocumentType::Gaf(GafVersion::Gaf2_1) => record.deserialize(None).map(RawRecord::Gaf2_1),
+            DocumentType::Gaf(GafVersion::Gaf2_2) => record.deserialize(None).map(RawRecord::Gaf2_2),
+            DocumentType::Gpad(GpadVersion::Gpad1_2) => record.deserialize(None).map(RawRecord::Gpad1_2),
+            DocumentType::Gpad(GpadVersion::Gpad2_0) => record.deserialize(None).map(RawRecord::Gpad2_0),
+            DocumentType::Gpi(GpiVersion::Gpi1_2) => record.deserialize(None).map(RawRecord::Gpi1_2),
+            DocumentType::Gpi(GpiVersion::Gpi2_0) => record.deserialize(None).map(RawRecord::Gpi2_0)
+        };
+
+        raw.map_err(|err| ParseError::Line(err.to_string()))
+    }
+
+    /// Converts this line into a `GoAssociation` through the base row of its format. GPI lines are not annotations, so
+    /// they are always an Err.
+    pub fn into_association(self, context: &Context) -> Result<model::GoAssociation, ParseErrors> {
+        match self {
+            RawRecord::Gaf2_1(raw) => model::convert_raw::<RawGaf2_1Record, BaseGaf2_1Row>(raw, context),
+            RawRecord::Gaf2_2(raw) => model::convert_raw::<RawGaf2_2Record, BaseGaf2_2Row>(raw, context),
+            RawRecord::Gpad1_2(raw) => model::convert_raw::<RawGpad1_2Record, BaseGpad1_2Row>(raw, context),
+            RawRecord::Gpad2_0(raw) => model::convert_raw::<RawGpad2_0Record, BaseGpad2_0Row>(raw, context),
+            RawRecord::Gpi1_2(_) | RawRecord::Gpi2_0(_) => Err(ParseError::Line("GPI lines are gene products, not annotations".into()).into())
+        }
+    }
+
+    /// Writes `association` as a line of `document_type`. This is an Err if the `context` is missing something the
+    /// format needs, like a relation label or the aspect of the GO term, and for GPI, which can't be made from a
+    /// `GoAssociation`.
+    pub fn from_association(association: model::GoAssociation, context: &Context, document_type: DocumentType) -> Result<RawRecord, ParseError> {
+        check_writable(&association, context, document_type)?;
+        let raw = match document_type {
+            DocumentType::Gaf(GafVersion::Gaf2_1) => RawRecord::Gaf2_1(BaseGaf2_1Row::from((association, context)).into()),
+            DocumentType::Gaf(GafVersion::Gaf2_2) => RawRecord::Gaf2_2(BaseGaf2_2Row::from((association, context)).into()),
+            DocumentType::Gpad(GpadVersion::Gpad1_2) => RawRecord::Gpad1_2(BaseGpad1_2Row::from((association, context)).into()),
+            DocumentType::Gpad(GpadVersion::Gpad2_0) => RawRecord::Gpad2_0(BaseGpad2_0Row::from((association, context)).into()),
+            DocumentType::Gpi(_) => unreachable!("check_writable rejects GPI")
+        };
+        Ok(raw)
+    }
+
+    pub fn document_type(&self) -> DocumentType {
+        match self {
+            RawRecord::Gaf2_1(_) => DocumentType::Gaf(GafVersion::Gaf2_1),
+            RawRecord::Gaf2_2(_) => DocumentType::Gaf(GafVersion::Gaf2_2),
+            RawRecord::Gpad1_2(_) => DocumentType::Gpad(GpadVersion::Gpad1_2),
+            RawRecord::Gpad2_0(_) => DocumentType::Gpad(GpadVersion::Gpad2_0),
+            RawRecord::Gpi1_2(_) => DocumentType::Gpi(GpiVersion::Gpi1_2),
+            RawRecord::Gpi2_0(_) => DocumentType::Gpi(GpiVersion::Gpi2_0)
+        }
+    }
+}
+
+///
+/// The conversions from a `GoAssociation` into the GAF and GPAD 1.2 base rows look up relation labels, evidence codes,
+/// and aspects in the `context`, and panic if they are missing. This finds those problems first.
+fn check_writable(association: &model::GoAssociation, context: &Context, document_type: DocumentType) -> Result<(), ParseError> {
+    if let DocumentType::Gpi(_) = document_type {
+        return Err(ParseError::Line(format!("Annotations can't be written as `{}`", document_type.version_comment())));
+    }
+
+    if let DocumentType::Gaf(_) | DocumentType::Gpad(GpadVersion::Gpad1_2) = document_type {
+        let extension_relations = association.object_extension.items().iter()
+            .flat_map(|conjunction| conjunction.elements().iter().map(|cls| &cls.relation));
+        // GAF 2.1 leaves most relations out of the qualifier, so only the ones it keeps need a label
+        let relation = if document_type != DocumentType::Gaf(GafVersion::Gaf2_1) {
+            Some(&association.relation)
+        } else {
+            None
+        };
+        for relation in relation.into_iter().chain(extension_relations) {
+            if context.curie_to_label(relation).is_none() {
+                return Err(ParseError::Line(format!("No label for relation `{}`", relation)));
+            }
+        }
+    }
+
+    if let DocumentType::Gaf(_) = document_type {
+        if context.eco_mapping.curie_to_eco(&association.evidence.id).is_none() {
+            return Err(ParseError::Line(format!("No evidence code for `{}`", association.evidence.id)));
+        }
+
+        let aspect = association.aspect.or_else(|| {
+            context.uri_mapping.uri_for_curie(&association.object.id)
+                .and_then(|uri| context.ontology.get_node(uri))
+                .and_then(|node| node.aspect())
+        });
+        if aspect.is_none() {
+            return Err(ParseError::Line(format!("No aspect for `{}` in the ontology", association.object.id)));
+        }
+    }
+
+    Ok(())
+}
+
+impl fmt::Display for RawRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RawRecord::Gaf2_1(r) => write!(f, "{}", r),
+            RawRecord::Gaf2_2(r) => write!(f, "{}", r),
+            RawRecord::Gpad1_2(r) => write!(f, "{}", r),
+            RawRecord::Gpad2_0(r) => write!(f, "{}", r),
+            RawRecord::Gpi1_2(r) => write!(f, "{}", r),
+            RawRecord::Gpi2_0(r) => write!(f, "{}", r)
+        }
+    }
+}
+
 ///                         0       1       2      3                4       5       6       7       8           9           10     11      12      13       14      15       16
 #[derive(Debug, Clone, Deserialize, PartialEq, Serialize)]
 pub struct RawGaf2_1Record(String, String, String, Option<String>, String, String, String, String, char, Option<String>, String, String, String, String, String, String, Option<String>);
@@ -62,6 +348,24 @@ impl fmt::Display for RawGaf2_1Record {
     }
 }
 
+///                         0       1       2       3       4       5       6       7       8     9               10      11      12      13      14      15      16
+#[derive(Debug, Clone, Deserialize, PartialEq, Serialize)]
+pub struct RawGaf2_2Record(String, String, String, String, String, String, String, String, char, Option<String>, String, String, String, String, String, String, Option<String>);
+
+impl fmt::Display for RawGaf2_2Record {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn str_opt(opt: &Option<String>) -> &str {
+            match opt {
+                Some(s) => s,
+                None => ""
+            }
+        }
+        let col8 = self.8.to_string();
+        let row: Vec<&str> = vec![&self.0, &self.1, &self.2, &self.3, &self.4, &self.5, &self.6, &self.7, &col8, str_opt(&self.9), &self.10, &self.11, &self.12, &self.13, &self.14, &self.15, str_opt(&self.16)];
+        write!(f, "{}", row.join("\t"))
+    }
+}
+
 ///
 /// The Basic structure of a GAF row.
 /// Look in the fields.rs module for information about these types.
@@ -91,66 +395,49 @@ pub struct BaseGaf2_1Row(
 );
 
 impl TryFrom<RawGaf2_1Record> for BaseGaf2_1Row {
-    type Error = String;
-
-    fn try_from(gaf21_record: RawGaf2_1Record) -> Result<BaseGaf2_1Row, String> {
-        let base_row = 
-                NoSpaceString::try_from(gaf21_record.0.as_str())
-            .and_then(|f0|
-                NoSpaceString::try_from(gaf21_record.1.as_str())
-            .and_then(|f1|
-                NoSpaceString::try_from(gaf21_record.2.as_str())
-            .and_then(|f2| 
-                match &gaf21_record.3 {
-                    None => Ok(None),
-                    Some(f) => EitherOrBoth::try_from(f.as_str()).map(Some)
-                }
-            .and_then(|f3: Option<EitherOrBoth<Not, Label>>|
-                Curie::try_from(gaf21_record.4.as_str())
-            .and_then(|f4|
-                ListField::try_from(gaf21_record.5.as_str())
-            .and_then(|f5: ListField<Curie>| 
-                EcoCode::try_from(gaf21_record.6.as_str())
-            .and_then(|f6| 
-                ListField::try_from(gaf21_record.7.as_str())
-            .and_then(|f7: ListField<Curie>|
-                Aspect::try_from(gaf21_record.8)
-            .and_then(|f8|
-                match &gaf21_record.9 {
-                    None => Ok(None),
-                    Some(f) => PlainString::try_from(f.as_str()).map(Some)
-                }
-            .and_then(|f9|
-                ListField::try_from(gaf21_record.10.as_str())
-            .and_then(|f10: ListField<PlainString>|
-                PlainString::try_from(gaf21_record.11.as_str())
-            .and_then(|f11|
-                OneOrTwoItems::try_from(gaf21_record.12.as_str())
-            .and_then(|f12: OneOrTwoItems<Curie>|
-                fields::Date::try_from(gaf21_record.13.as_str())
-            .and_then(|f13|
-                NoSpaceString::try_from(gaf21_record.14.as_str())
-            .and_then(|f14|
-                ListField::try_from(gaf21_record.15.as_str())
-            .and_then(|f15: ListField<Conjunction<ClassExpression<Label, Curie>>>|
-                match &gaf21_record.16 {
-                    None => Ok(None),
-                    Some(f) => Curie::try_from(f.as_str()).map(Some)
-                }
-            .map(|f16|
-                BaseGaf2_1Row(f0, f1, f2, f3, f4, f5, f6, f7, f8, f9, f10, f11, f12, f13, f14, f15, f16)
-            )))))))))))))))));
-        
-        base_row
+    type Error = ParseErrors;
+
+    fn try_from(gaf21_record: RawGaf2_1Record) -> Result<BaseGaf2_1Row, ParseErrors> {
+        let mut errors = ParseErrors::new();
+        let f0 = errors.check(parse_column(1, "DB", gaf21_record.0.as_str(), form::NO_SPACES));
+        let f1 = errors.check(parse_column(2, "DB Object ID", gaf21_record.1.as_str(), form::NO_SPACES));
+        let f2 = errors.check(parse_column(3, "DB Object Symbol", gaf21_record.2.as_str(), form::NO_SPACES));
+        let f3: Option<Option<EitherOrBoth<Not, Label>>> = errors.check(parse_optional_column(4, "Qualifier", &gaf21_record.3, form::GAF_2_1_QUALIFIER));
+        let f4 = errors.check(parse_column(5, "GO ID", gaf21_record.4.as_str(), form::CURIE));
+        let f5: Option<ListField<Curie>> = errors.check(parse_column(6, "DB:Reference", gaf21_record.5.as_str(), form::CURIE_LIST));
+        let f6 = errors.check(parse_column(7, "Evidence Code", gaf21_record.6.as_str(), form::EVIDENCE_CODE));
+        let f7: Option<ListField<Curie>> = errors.check(parse_column(8, "With (or) From", gaf21_record.7.as_str(), form::CURIE_LIST));
+        let f8 = errors.check(Aspect::try_from(gaf21_record.8).map_err(|_| ParseError::column(9, "Aspect", gaf21_record.8.to_string(), ParseErrorKind::Malformed(form::ASPECT))));
+        let f9 = errors.check(parse_optional_column(10, "DB Object Name", &gaf21_record.9, form::TEXT));
+        let f10: Option<ListField<PlainString>> = errors.check(parse_column(11, "DB Object Synonym", gaf21_record.10.as_str(), form::TEXT_LIST));
+        let f11 = errors.check(parse_column(12, "DB Object Type", gaf21_record.11.as_str(), form::TEXT));
+        let f12: Option<OneOrTwoItems<Curie>> = errors.check(parse_column(13, "Taxon", gaf21_record.12.as_str(), form::GAF_TAXON));
+        let f13 = errors.check(parse_column_with(14, "Date", gaf21_record.13.as_str(), form::DATE, Date::from_gaf));
+        let f14 = errors.check(parse_column(15, "Assigned By", gaf21_record.14.as_str(), form::NO_SPACES));
+        let f15: Option<ListField<Conjunction<ClassExpression<Label, Curie>>>> = errors.check(parse_column(16, "Annotation Extension", gaf21_record.15.as_str(), form::EXTENSIONS));
+        let f16 = errors.check(parse_optional_column(17, "Gene Product Form ID", &gaf21_record.16, form::CURIE));
+
+        match (f0, f1, f2, f3, f4, f5, f6, f7, f8, f9, f10, f11, f12, f13, f14, f15, f16) {
+            (Some(f0), Some(f1), Some(f2), Some(f3), Some(f4), Some(f5), Some(f6), Some(f7), Some(f8), Some(f9), Some(f10), Some(f11), Some(f12), Some(f13), Some(f14), Some(f15), Some(f16)) =>
+                Ok(BaseGaf2_1Row(f0, f1, f2, f3, f4, f5, f6, f7, f8, f9, f10, f11, f12, f13, f14, f15, f16)),
+            _ => Err(errors)
+        }
     }
 }
 
+/// The only relations a GAF 2.1 qualifier may have, besides `NOT`.
+const GAF_2_1_RELATIONS: [&str; 2] = ["contributes_to", "colocalizes_with"];
+
 impl From<(model::GoAssociation, &Context)> for BaseGaf2_1Row {
+    /// Other relations than `GAF_2_1_RELATIONS` are left out of the qualifier, since GAF 2.1 doesn't allow them.
     fn from((association, context): (model::GoAssociation, &Context)) -> BaseGaf2_1Row {
-        let qualifier_field = if association.negated {
-            EitherOrBoth::Both(Not, context.curie_to_label(&association.relation).unwrap())
-        } else {
-            EitherOrBoth::Right(context.curie_to_label(&association.relation).unwrap())
+        let relation = context.curie_to_label(&association.relation)
+            .filter(|label| GAF_2_1_RELATIONS.contains(&label.0.as_str()));
+        let qualifier_field = match (association.negated, relation) {
+            (true, Some(relation)) => Some(EitherOrBoth::Both(Not, relation)),
+            (true, None) => Some(EitherOrBoth::Left(Not)),
+            (false, Some(relation)) => Some(EitherOrBoth::Right(relation)),
+            (false, None) => None
         };
 
         let aspect = match association.aspect {
@@ -190,10 +477,10 @@ impl From<(model::GoAssociation, &Context)> for BaseGaf2_1Row {
         });
 
         BaseGaf2_1Row(
-            NoSpaceString::new(association.subject.id.identifier),
             NoSpaceString::new(association.subject.id.namespace),
+            NoSpaceString::new(association.subject.id.identifier),
             association.subject.label,
-            Some(qualifier_field),
+            qualifier_field,
             association.object.id,
             association.evidence.has_supporting_reference,
             context.eco_mapping.curie_to_eco(&association.evidence.id).unwrap(),
@@ -239,6 +526,122 @@ impl From<BaseGaf2_1Row> for RawGaf2_1Record {
     }
 }
 
+///
+/// GAF 2.2 qualifiers must always carry a relation, optionally preceded by `NOT`:
+/// `enables` or `NOT|enables`. A bare `NOT` or an empty column is an error.
+fn relation_qualifier(column: usize, name: &'static str, entity: &str) -> Result<EitherOrBoth<Not, Label>, ParseError> {
+    parse_column(column, name, entity, form::QUALIFIER).and_then(|qualifier: EitherOrBoth<Not, Label>| {
+        match qualifier {
+            EitherOrBoth::Right(ref label) | EitherOrBoth::Both(_, ref label) if !label.0.is_empty() => Ok(qualifier),
+            _ => Err(ParseError::column(column, name, entity, ParseErrorKind::Malformed(form::QUALIFIER)))
+        }
+    })
+}
+
+///
+/// The Basic structure of a GAF 2.2 row.
+/// 
+/// This is the same as `BaseGaf2_1Row`, except that the Qualifier (3) is mandatory and must contain
+/// a relation label, optionally negated with `NOT`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BaseGaf2_2Row(
+    NoSpaceString,                              /// 0
+    NoSpaceString,                              /// 1
+    NoSpaceString,                              /// 2
+    EitherOrBoth<Not, Label>,                   /// 3
+    Curie,                                      /// 4
+    ListField<Curie>,                           /// 5
+    EcoCode,                                    /// 6
+    ListField<Curie>,                           /// 7
+    Aspect,                                     /// 8
+    Option<PlainString>,                        /// 9
+    ListField<PlainString>,                     /// 10
+    PlainString,                                /// 11
+    OneOrTwoItems<Curie>,                       /// 12
+    fields::Date,                               /// 13
+    NoSpaceString,                              /// 14
+    ListField<Conjunction<ClassExpression<Label, Curie>>>, /// 15
+    Option<Curie>                               // 16
+);
+
+impl TryFrom<RawGaf2_2Record> for BaseGaf2_2Row {
+    type Error = ParseErrors;
+
+    fn try_from(gaf22_record: RawGaf2_2Record) -> Result<BaseGaf2_2Row, ParseErrors> {
+        let mut errors = ParseErrors::new();
+        let f0 = errors.check(parse_column(1, "DB", gaf22_record.0.as_str(), form::NO_SPACES));
+        let f1 = errors.check(parse_column(2, "DB Object ID", gaf22_record.1.as_str(), form::NO_SPACES));
+        let f2 = errors.check(parse_column(3, "DB Object Symbol", gaf22_record.2.as_str(), form::NO_SPACES));
+        let f3 = errors.check(relation_qualifier(4, "Qualifier", gaf22_record.3.as_str()));
+        let f4 = errors.check(parse_column(5, "GO ID", gaf22_record.4.as_str(), form::CURIE));
+        let f5: Option<ListField<Curie>> = errors.check(parse_column(6, "DB:Reference", gaf22_record.5.as_str(), form::CURIE_LIST));
+        let f6 = errors.check(parse_column(7, "Evidence Code", gaf22_record.6.as_str(), form::EVIDENCE_CODE));
+        let f7: Option<ListField<Curie>> = errors.check(parse_column(8, "With (or) From", gaf22_record.7.as_str(), form::CURIE_LIST));
+        let f8 = errors.check(Aspect::try_from(gaf22_record.8).map_err(|_| ParseError::column(9, "Aspect", gaf22_record.8.to_string(), ParseErrorKind::Malformed(form::ASPECT))));
+        let f9 = errors.check(parse_optional_column(10, "DB Object Name", &gaf22_record.9, form::TEXT));
+        let f10: Option<ListField<PlainString>> = errors.check(parse_column(11, "DB Object Synonym", gaf22_record.10.as_str(), form::TEXT_LIST));
+        let f11 = errors.check(parse_column(12, "DB Object Type", gaf22_record.11.as_str(), form::TEXT));
+        let f12: Option<OneOrTwoItems<Curie>> = errors.check(parse_column(13, "Taxon", gaf22_record.12.as_str(), form::GAF_TAXON));
+        let f13 = errors.check(parse_column_with(14, "Date", gaf22_record.13.as_str(), form::DATE, Date::from_gaf));
+        let f14 = errors.check(parse_column(15, "Assigned By", gaf22_record.14.as_str(), form::NO_SPACES));
+        let f15: Option<ListField<Conjunction<ClassExpression<Label, Curie>>>> = errors.check(parse_column(16, "Annotation Extension", gaf22_record.15.as_str(), form::EXTENSIONS));
+        let f16 = errors.check(parse_optional_column(17, "Gene Product Form ID", &gaf22_record.16, form::CURIE));
+
+        match (f0, f1, f2, f3, f4, f5, f6, f7, f8, f9, f10, f11, f12, f13, f14, f15, f16) {
+            (Some(f0), Some(f1), Some(f2), Some(f3), Some(f4), Some(f5), Some(f6), Some(f7), Some(f8), Some(f9), Some(f10), Some(f11), Some(f12), Some(f13), Some(f14), Some(f15), Some(f16)) =>
+                Ok(BaseGaf2_2Row(f0, f1, f2, f3, f4, f5, f6, f7, f8, f9, f10, f11, f12, f13, f14, f15, f16)),
+            _ => Err(errors)
+        }
+    }
+}
+
+impl From<(model::GoAssociation, &Context)> for BaseGaf2_2Row {
+    /// The same as converting to `BaseGaf2_1Row`, but with the Qualifier always carrying the relation label. Like that
+    /// conversion, this panics on anything `check_writable` rejects.
+    fn from((association, context): (model::GoAssociation, &Context)) -> BaseGaf2_2Row {
+        let negated = association.negated;
+        let relation = context.curie_to_label(&association.relation)
+            .expect("check_writable ensures the relation has a label");
+        let BaseGaf2_1Row(f0, f1, f2, _, f4, f5, f6, f7, f8, f9, f10, f11, f12, f13, f14, f15, f16) = BaseGaf2_1Row::from((association, context));
+
+        let qualifier_field = if negated {
+            EitherOrBoth::Both(Not, relation)
+        } else {
+            EitherOrBoth::Right(relation)
+        };
+
+        BaseGaf2_2Row(f0, f1, f2, qualifier_field, f4, f5, f6, f7, f8, f9, f10, f11, f12, f13, f14, f15, f16)
+    }
+}
+
+impl From<BaseGaf2_2Row> for RawGaf2_2Record {
+    fn from(base_row: BaseGaf2_2Row) -> RawGaf2_2Record {
+        RawGaf2_2Record(
+            base_row.0.value,
+            base_row.1.value,
+            base_row.2.value,
+            match base_row.3 {
+                EitherOrBoth::Left(l) => l.string(),
+                EitherOrBoth::Right(r) => r.0,
+                EitherOrBoth::Both(l, r) => format!("{}|{}", l.string(), r.0)
+            },
+            base_row.4.string(),
+            base_row.5.to_string(),
+            base_row.6.to_string(),
+            base_row.7.to_string(),
+            base_row.8.as_char(),
+            base_row.9.map(|p| p.0),
+            base_row.10.to_string(),
+            base_row.11.0,
+            base_row.12.to_string(),
+            base_row.13.to_string(),
+            base_row.14.value,
+            base_row.15.to_string(),
+            base_row.16.map(|c| c.to_string())
+        )
+    }
+}
+
 
 
 #[cfg(test)]
@@ -246,7 +649,6 @@ mod test {
     use super::*;
 
     use csv::{ReaderBuilder};
-    use chrono::TimeZone;
 
     #[test]
     fn test_reader_into_raw_2_1_gaf() {
@@ -299,7 +701,7 @@ mod test {
 
         let raw: RawGaf2_1Record = gaf_reader.deserialize().next().unwrap().unwrap();
         // println!("raw: {:?}", raw);
-        let base: Result<BaseGaf2_1Row, String> = BaseGaf2_1Row::try_from(raw);
+        let base: Result<BaseGaf2_1Row, ParseErrors> = BaseGaf2_1Row::try_from(raw);
         // println!("{:?}", base);
         
         let expected = BaseGaf2_1Row(
@@ -316,7 +718,7 @@ mod test {
             ListField::new(vec![PlainString("tw".into()), PlainString("Wnt-7a".into())]),
             PlainString("protein".into()),
             OneOrTwoItems::One(Curie::new("taxon", "10090")),
-            fields::Date{date: chrono::Utc.ymd(2018, 7, 11)},
+            fields::Date::from_gaf("20180711").unwrap(),
             NoSpaceString::new("SynGO"),
             ListField::new(vec![
                 Conjunction::new(vec![
@@ -329,4 +731,142 @@ mod test {
 
         assert_eq!(base.unwrap(), expected)
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_convert_gaf_2_2_to_base_row() {
+        let example = "MGI\tMGI:98961\tWnt7a\tNOT|involved_in\tGO:0099175\tMGI:MGI:5014434|PMID:21670302\tIMP\t\tP\twingless-type MMTV integration site family, member 7A\ttw|Wnt-7a\tprotein\ttaxon:10090\t20180711\tSynGO\toccurs_in(GO:0098978)\t";
+        let mut gaf_reader = ReaderBuilder::new()
+            .delimiter(b'\t')
+            .flexible(true)
+            .has_headers(false)
+            .comment(Some(b'!'))
+            .from_reader(example.as_bytes());
+
+        let raw: RawGaf2_2Record = gaf_reader.deserialize().next().unwrap().unwrap();
+        let base = BaseGaf2_2Row::try_from(raw).unwrap();
+
+        assert_eq!(base.3, EitherOrBoth::Both(Not, Label("involved_in".into())));
+        assert_eq!(base.13, fields::Date::from_gaf("20180711").unwrap());
+    }
+
+    #[test]
+    fn test_gaf_2_2_qualifier_requires_relation() {
+        let mut raw = RawGaf2_2Record(
+            "MGI".into(), "MGI:98961".into(), "Wnt7a".into(), "NOT".into(), "GO:0099175".into(), "PMID:21670302".into(),
+            "IMP".into(), "".into(), 'P', None, "".into(), "protein".into(), "taxon:10090".into(), "20180711".into(),
+            "SynGO".into(), "".into(), None);
+        assert!(BaseGaf2_2Row::try_from(raw.clone()).is_err());
+
+        raw.3 = "".into();
+        assert!(BaseGaf2_2Row::try_from(raw.clone()).is_err());
+
+        raw.3 = "involved_in".into();
+        assert!(BaseGaf2_2Row::try_from(raw).is_ok());
+    }
+
+    #[test]
+    fn test_all_column_errors_are_kept() {
+        let raw = RawGaf2_1Record(
+            "MGI".into(), "MGI:98961".into(), "Wnt7a".into(), None, "GO0099175".into(), "PMID:21670302".into(),
+            "IMP".into(), "".into(), 'X', None, "".into(), "protein".into(), "taxon:10090".into(), "2018-13-11".into(),
+            "SynGO".into(), "".into(), None);
+        let errors = BaseGaf2_1Row::try_from(raw).unwrap_err();
+
+        let columns: Vec<&str> = errors.errors().iter().filter_map(ParseError::column_name).collect();
+        assert_eq!(columns, vec!["GO ID", "Aspect", "Date"]);
+    }
+
+    #[test]
+    fn test_write_gaf_2_2_from_association() {
+        let example = "MGI\tMGI:98961\tWnt7a\tinvolved_in\tGO:0099175\tMGI:MGI:5014434|PMID:21670302\tIMP\t\tP\t\ttw|Wnt-7a\tprotein\ttaxon:10090\t20180711\tSynGO\toccurs_in(GO:0098978)\t";
+        let mut gaf_reader = ReaderBuilder::new()
+            .delimiter(b'\t')
+            .flexible(true)
+            .has_headers(false)
+            .from_reader(example.as_bytes());
+
+        let context = Context::default();
+        let raw: RawGaf2_2Record = gaf_reader.deserialize().next().unwrap().unwrap();
+        let association = model::convert_raw::<RawGaf2_2Record, BaseGaf2_2Row>(raw, &context).unwrap();
+        assert_eq!(association.relation, Curie::new("RO", "0002331"));
+
+        let written: RawGaf2_2Record = BaseGaf2_2Row::from((association, &context)).into();
+        assert_eq!(written.to_string(), example);
+    }
+
+    #[test]
+    fn test_write_gaf_2_1_round_trip() {
+        let context = Context::default();
+        let read = |line: &str, document_type: DocumentType| {
+            let record = csv::StringRecord::from(line.split('\t').collect::<Vec<&str>>());
+            RawRecord::from_record(&record, document_type).unwrap().into_association(&context).unwrap()
+        };
+        let gaf_2_2 = "MGI\tMGI:98961\tWnt7a\tNOT|involved_in\tGO:0099175\tPMID:21670302\tIMP\t\tP\t\t\tprotein\ttaxon:10090\t20180711\tSynGO\t\t";
+        let gaf_2_1 = DocumentType::Gaf(GafVersion::Gaf2_1);
+
+        let written = RawRecord::from_association(read(gaf_2_2, DocumentType::Gaf(GafVersion::Gaf2_2)), &context, gaf_2_1).unwrap().to_string();
+        assert_eq!(written.split('\t').nth(3), Some("NOT"));
+        let reread = read(&written, gaf_2_1);
+        assert!(reread.negated);
+        assert_eq!(reread.object.id, Curie::new("GO", "0099175"));
+
+        let contributes = "MGI\tMGI:98961\tWnt7a\tcontributes_to\tGO:0003674\tPMID:21670302\tIMP\t\tF\t\t\tprotein\ttaxon:10090\t20180711\tSynGO\t\t";
+        let written = RawRecord::from_association(read(contributes, gaf_2_1), &context, gaf_2_1).unwrap().to_string();
+        assert_eq!(written, contributes);
+        assert_eq!(read(&written, gaf_2_1).relation, Curie::new("RO", "0002326"));
+    }
+
+    #[test]
+    fn test_document_from_header() {
+        let comments = vec!["gaf-version: 2.2".to_string(), "generated-by: MGI".to_string(), "date-generated: 2020-01-01".to_string(), " Contact: mgi-help".to_string()];
+        let document: AnnotationDocument<RawGaf2_2Record> = AnnotationDocument::from_header(comments).unwrap();
+        assert_eq!(document.document_type(), DocumentType::Gaf(GafVersion::Gaf2_2));
+
+        let header = document.header();
+        assert_eq!(header[0], "!gaf-version: 2.2");
+        assert_eq!(header[1], "!generated-by: fast-go-annotations");
+        assert!(header[2].starts_with("!date-generated: "));
+        assert_eq!(&header[3..], &["! Contact: mgi-help".to_string()]);
+
+        let no_version: Option<AnnotationDocument<RawGaf2_2Record>> = AnnotationDocument::from_header(vec!["gaf-version: 3.0".into()]);
+        assert!(no_version.is_none());
+    }
+
+    #[test]
+    fn test_document_type_from_columns() {
+        let gaf_2_1 = "MGI\tMGI:98961\tWnt7a\tNOT\tGO:0099175\tPMID:21670302\tIMP\t\tP\t\t\tprotein\ttaxon:10090\t20180711\tSynGO\t\t";
+        let gaf_2_2 = "MGI\tMGI:98961\tWnt7a\tNOT|involved_in\tGO:0099175\tPMID:21670302\tIMP\t\tP\t\t\tprotein\ttaxon:10090\t20180711\tSynGO\t\t";
+        let gpad_1_2 = "MGI\tMGI:98961\tenables\tGO:0003674\tPMID:21670302\tECO:0000314\t\t\t20180711\tSynGO\t\t";
+        let gpad_2_0 = "MGI:MGI:98961\t\tRO:0002327\tGO:0003674\tPMID:21670302\tECO:0000314\t\t\t2018-07-11\tSynGO\t\t";
+
+        let columns = |line: &'static str| line.split('\t').collect::<Vec<&str>>();
+        assert_eq!(DocumentType::from_columns(&columns(gaf_2_1)), Some(DocumentType::Gaf(GafVersion::Gaf2_1)));
+        assert_eq!(DocumentType::from_columns(&columns(gaf_2_2)), Some(DocumentType::Gaf(GafVersion::Gaf2_2)));
+        assert_eq!(DocumentType::from_columns(&columns(gpad_1_2)), Some(DocumentType::Gpad(GpadVersion::Gpad1_2)));
+        assert_eq!(DocumentType::from_columns(&columns(gpad_2_0)), Some(DocumentType::Gpad(GpadVersion::Gpad2_0)));
+        assert_eq!(DocumentType::from_columns(&["MGI", "MGI:98961"]), None);
+    }
+
+    #[test]
+    fn test_raw_record_column_count() {
+        let record = csv::StringRecord::from(vec!["MGI", "MGI:98961", "enables"]);
+        let raw = RawRecord::from_record(&record, DocumentType::Gaf(GafVersion::Gaf2_2));
+        assert_eq!(raw, Err(ParseError::ColumnCount { min: 15, max: 17, found: 3 }));
+        assert_eq!(raw.unwrap_err().to_string(), "expected 15 to 17 columns, but found 3");
+    }
+
+    #[test]
+    fn test_raw_record_without_optional_columns() {
+        let gaf = "MGI\tMGI:98961\tWnt7a\tinvolved_in\tGO:0099175\tPMID:21670302\tIMP\t\tP\t\t\tprotein\ttaxon:10090\t20180711\tSynGO";
+        let record = csv::StringRecord::from(gaf.split('\t').collect::<Vec<&str>>());
+        match RawRecord::from_record(&record, DocumentType::Gaf(GafVersion::Gaf2_2)).unwrap() {
+            RawRecord::Gaf2_2(raw) => assert_eq!((raw.15.as_str(), raw.16), ("", None)),
+            other => panic!("expected a GAF 2.2 line, but got {:?}", other)
+        }
+        assert_eq!(DocumentType::from_columns(&gaf.split('\t').collect::<Vec<&str>>()), Some(DocumentType::Gaf(GafVersion::Gaf2_2)));
+
+        let gpad = "MGI:MGI:98961\t\tRO:0002327\tGO:0003674\tPMID:21670302\tECO:0000314\t\t\t2018-07-11\tSynGO";
+        let record = csv::StringRecord::from(gpad.split('\t').collect::<Vec<&str>>());
+        assert!(RawRecord::from_record(&record, DocumentType::Gpad(GpadVersion::Gpad2_0)).is_ok());
+    }
+}
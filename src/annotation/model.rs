@@ -6,9 +6,7 @@
 //! format.
 //! 
 //! Parsing an annotatino line into a `GoAssociation` can fail, so any attempt to convert will return a
-//! Result<GoAssociation, String>, with the error message being a string, if there is an error during conversion.
-//! 
-//! Future versions could make the error type smarter.
+//! Result<GoAssociation, ParseErrors>, with a `ParseError` for each column that failed saying why.
 //! 
 //! Converting from a `GoAssociation` into some other annotation format should in general not fail, so there is
 //! no Error type.
@@ -35,7 +33,7 @@
 //!     then convert to GoAssociations automatically at this point.
 //! 
 //! The high-level function `parse_annotation` will generically take anything that implements `ConvertableAnnotation`
-//! and turn it into a Result<GoAssociation, String>. This is what we use to ultimately produce the validation and rule
+//! and turn it into a Result<GoAssociation, ParseErrors>. This is what we use to ultimately produce the validation and rule
 //! reports.
 //! 
 //! The `Context` object contains various metadata that is used to create `GoAssociation`s from different sources. Namely
@@ -49,11 +47,12 @@ use std::convert::TryInto;
 
 use super::*;
 use crate::annotation::fields::*;
+use crate::annotation::error::{ParseError, ParseErrors};
 use crate::meta::Context;
 
 pub struct AnnotationWithContext<'a, A>(pub A, pub &'a Context);
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Term {
     pub id: Curie,
     pub taxon: Option<Curie>
@@ -67,7 +66,7 @@ impl Term {
     }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Subject {
     pub id: Curie,
     pub label: NoSpaceString,
@@ -89,7 +88,7 @@ impl Default for Subject {
     }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Evidence {
     pub id: Curie,
     pub has_supporting_reference: ListField<Curie>,
@@ -131,7 +130,7 @@ impl Default for Metadata {
             aspect: None,
             interacting_taxon: None,
             provided_by: NoSpaceString::new("Test"),
-            date: fields::Date::try_from("20200101").unwrap(),
+            date: fields::Date::from_gaf("20200101").unwrap(),
             properties: ListField::new(vec![])
         }
     }
@@ -179,7 +178,8 @@ pub trait HasExtensions<Error> {
     fn extensions(&self, context: &Context) -> Result<Extensions, Error>;
 }
 
-#[derive(Clone, Debug, PartialEq)]
+/// Serializes with serde as one JSON object per association, with Curies as strings and extensions as structured objects.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct GoAssociation {
     // source_line: RawGaf2_1Record,
     pub subject: Subject,
@@ -198,29 +198,29 @@ pub struct GoAssociation {
 
 
 pub trait ConvertableAnnotation: 
-    HasEvidence<String> + 
-    HasSubject<String> +
-    HasRelation<String> +
-    HasTerm<String> +
-    HasMetadata<String> +
-    HasExtensions<String> {}
+    HasEvidence<ParseError> + 
+    HasSubject<ParseError> +
+    HasRelation<ParseError> +
+    HasTerm<ParseError> +
+    HasMetadata<ParseError> +
+    HasExtensions<ParseError> {}
 
 /// Provide Blanket implementation of ConvertableAnnotation for anything
 /// that implements HasEvidence, HasSubject, HasRelation, HasTerm, HasMetadata,
 /// and HasExtensions
 impl<A> ConvertableAnnotation for A
     where A:
-        HasEvidence<String> + 
-        HasSubject<String> +
-        HasRelation<String> +
-        HasTerm<String> +
-        HasMetadata<String> +
-        HasExtensions<String> { }
+        HasEvidence<ParseError> + 
+        HasSubject<ParseError> +
+        HasRelation<ParseError> +
+        HasTerm<ParseError> +
+        HasMetadata<ParseError> +
+        HasExtensions<ParseError> { }
 
 
-pub fn convert_raw<R, B>(raw: R, context: &Context) -> Result<GoAssociation, String>
+pub fn convert_raw<R, B>(raw: R, context: &Context) -> Result<GoAssociation, ParseErrors>
     where
-        R: TryInto<B, Error=String>,
+        R: TryInto<B, Error=ParseErrors>,
         B: ConvertableAnnotation {
 
     raw.try_into().and_then(|b: B| parse_annotation(b, context))
@@ -250,28 +250,27 @@ impl From<(Subject, Relation, Term, Evidence, Metadata, Extensions)> for GoAssoc
 impl<'a, Annotation> TryFrom<&AnnotationWithContext<'_, Annotation>> for GoAssociation 
     where Annotation: ConvertableAnnotation {
 
-    type Error = String;
+    type Error = ParseErrors;
 
+    /// Each part of the association is converted independently, so the error has every column that failed.
     fn try_from(AnnotationWithContext(annotation, context): &AnnotationWithContext<Annotation>) -> Result<GoAssociation, Self::Error> {
-
-        annotation.subject(context)
-            .and_then(|subject|
-                annotation.relation(context)
-            .and_then(|relation|
-                annotation.term(context)
-            .and_then(|term|
-                annotation.evidence(context)
-            .and_then(|evidence|
-                annotation.metadata(context)
-            .and_then(|metadata|
-                annotation.extensions(context)
-            .map(|extensions|
-                GoAssociation::from((subject, relation, term, evidence, metadata, extensions)
-            )))))))
+        let mut errors = ParseErrors::new();
+        let subject = errors.check(annotation.subject(context));
+        let relation = errors.check(annotation.relation(context));
+        let term = errors.check(annotation.term(context));
+        let evidence = errors.check(annotation.evidence(context));
+        let metadata = errors.check(annotation.metadata(context));
+        let extensions = errors.check(annotation.extensions(context));
+
+        match (subject, relation, term, evidence, metadata, extensions) {
+            (Some(subject), Some(relation), Some(term), Some(evidence), Some(metadata), Some(extensions)) =>
+                Ok(GoAssociation::from((subject, relation, term, evidence, metadata, extensions))),
+            _ => Err(errors)
+        }
     }
 }
 
-pub fn parse_annotation<A: ConvertableAnnotation>(annotation: A, context: &Context) -> Result<GoAssociation, String> {
+pub fn parse_annotation<A: ConvertableAnnotation>(annotation: A, context: &Context) -> Result<GoAssociation, ParseErrors> {
     let annotation_with_context = AnnotationWithContext(annotation, context);
     GoAssociation::try_from(&annotation_with_context)
 }
@@ -280,7 +279,6 @@ pub fn parse_annotation<A: ConvertableAnnotation>(annotation: A, context: &Conte
 #[cfg(test)]
 mod test {
     use super::*;
-    use chrono::TimeZone;
 
     #[test]
     fn test_generic_parse() {
@@ -298,7 +296,7 @@ mod test {
             ListField::new(vec![PlainString("tw".into()), PlainString("Wnt-7a".into())]),
             PlainString("protein".into()),
             OneOrTwoItems::One(Curie::new("taxon", "10090")),
-            fields::Date{date: chrono::Utc.ymd(2018, 7, 11)},
+            fields::Date::from_gaf("20180711").unwrap(),
             NoSpaceString::new("SynGO"),
             ListField::new(vec![
                 Conjunction::new(vec![
@@ -339,10 +337,31 @@ mod test {
                 ])
             ]),
             provided_by: NoSpaceString::new("SynGO"),
-            date: fields::Date{date: chrono::Utc.ymd(2018, 7, 11)},
+            date: fields::Date::from_gaf("20180711").unwrap(),
             properties: ListField::new(vec![])
         };
 
         assert_eq!(parsed.unwrap(), expected);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_json_round_trip() {
+        let example = "MGI\tMGI:98961\tWnt7a\t\tGO:0099175\tPMID:21670302\tIMP\t\tP\t\ttw\tprotein\ttaxon:10090\t20180711\tSynGO\toccurs_in(GO:0098978)\t";
+        let mut gaf_reader = csv::ReaderBuilder::new()
+            .delimiter(b'\t')
+            .has_headers(false)
+            .from_reader(example.as_bytes());
+        let raw: RawGaf2_1Record = gaf_reader.deserialize().next().unwrap().unwrap();
+        let association = convert_raw::<RawGaf2_1Record, BaseGaf2_1Row>(raw, &Context::default()).unwrap();
+
+        let json = serde_json::to_value(&association).unwrap();
+        assert_eq!(json["subject"]["id"], "MGI:MGI:98961");
+        assert_eq!(json["aspect"], "P");
+        assert_eq!(json["date"], "2018-07-11");
+        assert_eq!(json["object_extension"][0][0]["relation"], "BFO:0000066");
+        assert_eq!(json["object_extension"][0][0]["filler"], "GO:0098978");
+
+        let back: GoAssociation = serde_json::from_value(json).unwrap();
+        assert_eq!(back, association);
+    }
+}
@@ -7,8 +7,13 @@ extern crate json_ld;
 use std::process;
 use std::io::Write;
 use std::fs::File;
+use std::io::{self, BufWriter};
+use std::convert::TryFrom;
+use std::collections::HashSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
 
-use clap::{Arg, App};
+use clap::{Arg, App, AppSettings, ArgMatches, SubCommand};
 
 pub mod ontology;
 pub mod annotation;
@@ -18,11 +23,14 @@ pub mod resource;
 pub mod report;
 pub mod validate;
 
-fn main() {
-    // println!("Hello, world!");
+use annotation::{AnnotationDocument, DocumentType, GafVersion, GpadVersion, GpiVersion, RawRecord};
+use annotation::gpi::{GeneProduct, RawGpi1_2Record, RawGpi2_0Record};
+use annotation::model;
 
+fn main() {
     let matches = App::new("Fast GO Annotation Parser")
         .version("0.1.0")
+        .setting(AppSettings::SubcommandsNegateReqs)
         .arg(Arg::with_name("ontology")
             .short("r")
             .long("ontology")
@@ -37,14 +45,29 @@ fn main() {
             .help("Path to JSON-LD URI Context Mapping")
             .takes_value(true)
             .required(true))
+        .arg(Arg::with_name("gpi")
+            .long("gpi")
+            .value_name("PATH")
+            .help("Path to GPI file, used to fill in gene product information for GPAD")
+            .takes_value(true)
+            .required(false))
         .arg(Arg::with_name("annotation")
             .short("f")
             .long("input-file")
+            .help("Annotation file to read, or `-` for stdin. With --out-dir, several files, directories, or glob patterns")
             .takes_value(true)
+            .multiple(true)
             .required(true))
+        .arg(Arg::with_name("name")
+            .long("name")
+            .value_name("NAME")
+            .help("Name for the report, instead of the input file name. With --out-dir, the name of the index")
+            .takes_value(true)
+            .required(false))
         .arg(Arg::with_name("out")
             .short("o")
             .long("out")
+            .help("Path to write valid annotations to, or `-` for stdout")
             .takes_value(true)
             .required(false))
         .arg(Arg::with_name("report-md")
@@ -55,53 +78,419 @@ fn main() {
             .long("report-json")
             .takes_value(true)
             .required(false))
+        .arg(Arg::with_name("out-jsonl")
+            .long("out-jsonl")
+            .value_name("PATH")
+            .help("Write each valid annotation as a JSON object per line")
+            .takes_value(true)
+            .required(false))
+        .arg(Arg::with_name("rules-config")
+            .long("rules-config")
+            .value_name("PATH")
+            .help("YAML or JSON file of rules to disable, rule severities, and overrides for groups")
+            .takes_value(true)
+            .required(false))
+        .arg(Arg::with_name("rule-metadata")
+            .long("rule-metadata")
+            .value_name("DIR")
+            .help("Directory of go-site gorule-*.md files for rule titles, severities, and the rules not implemented here")
+            .takes_value(true)
+            .required(false))
+        .arg(Arg::with_name("disable-rule")
+            .long("disable-rule")
+            .value_name("RULE")
+            .help("Don't run the rule, like gorule-0000002")
+            .takes_value(true)
+            .multiple(true)
+            .number_of_values(1)
+            .required(false))
+        .arg(Arg::with_name("rule-severity")
+            .long("rule-severity")
+            .value_name("RULE=LEVEL")
+            .help("Report the rule at `warning` or `error` when it doesn't pass, like gorule-0000020=error")
+            .takes_value(true)
+            .multiple(true)
+            .number_of_values(1)
+            .required(false))
+        .arg(Arg::with_name("out-dir")
+            .long("out-dir")
+            .value_name("DIR")
+            .help("Write the valid annotations and reports of each input file to DIR, with an index of all of them")
+            .takes_value(true)
+            .conflicts_with_all(&["out", "report-md", "report-json", "out-jsonl"])
+            .required(false))
+        .arg(Arg::with_name("gorefs")
+            .long("gorefs")
+            .value_name("PATH")
+            .help("go-site GO_REF metadata, as a directory of goref-*.md files or a YAML file, to check references against")
+            .takes_value(true)
+            .required(false))
+        .arg(Arg::with_name("today")
+            .long("today")
+            .value_name("YYYYMMDD")
+            .help("The date to check annotation ages against, instead of the current date")
+            .takes_value(true)
+            .required(false))
+        .arg(Arg::with_name("iea-max-age")
+            .long("iea-max-age")
+            .value_name("DAYS")
+            .help("Report IEA annotations older than this many days (gorule-0000029). Defaults to 365")
+            .takes_value(true)
+            .required(false))
+        .arg(Arg::with_name("threads")
+            .short("j")
+            .long("threads")
+            .value_name("N")
+            .help("Validate lines in parallel on N threads, or 0 for one per CPU")
+            .takes_value(true)
+            .required(false))
+        .subcommand(SubCommand::with_name("convert")
+            .about("Converts annotations from one format to another")
+            .arg(Arg::with_name("ontology")
+                .short("r")
+                .long("ontology")
+                .value_name("PATH")
+                .help("Path to OBO JSON Ontology file, used to find the Aspect when converting to GAF")
+                .takes_value(true)
+                .required(false))
+            .arg(Arg::with_name("context")
+                .short("c")
+                .long("context")
+                .value_name("PATH")
+                .help("Path to JSON-LD URI Context Mapping")
+                .takes_value(true)
+                .required(true))
+            .arg(Arg::with_name("gpi")
+                .long("gpi")
+                .value_name("PATH")
+                .help("Path to GPI file, used to fill in gene product information for GPAD")
+                .takes_value(true)
+                .required(false))
+            .arg(Arg::with_name("annotation")
+                .short("f")
+                .long("input-file")
+                .help("Annotation file to read, or `-` for stdin")
+                .takes_value(true)
+                .required(true))
+            .arg(Arg::with_name("name")
+                .long("name")
+                .value_name("NAME")
+                .help("Name for the report, instead of the input file name")
+                .takes_value(true)
+                .required(false))
+            .arg(Arg::with_name("to")
+                .short("t")
+                .long("to")
+                .help("Format to convert to")
+                .possible_values(&["gaf-2.1", "gaf-2.2", "gpad-1.2", "gpad-2.0"])
+                .takes_value(true)
+                .required(true))
+            .arg(Arg::with_name("out")
+                .short("o")
+                .long("out")
+                .help("Path to write converted annotations to, or `-` for stdout")
+                .takes_value(true)
+                .required(true))
+            .arg(Arg::with_name("gpi-out")
+                .long("gpi-out")
+                .value_name("PATH")
+                .help("Also write a GPI of the gene products in the annotations")
+                .takes_value(true)
+                .required(false))
+            .arg(Arg::with_name("out-jsonl")
+                .long("out-jsonl")
+                .value_name("PATH")
+                .help("Also write each converted annotation as a JSON object per line")
+                .takes_value(true)
+                .required(false))
+            .arg(Arg::with_name("rules")
+                .long("rules")
+                .help("Run the GO rules, dropping annotations with errors")
+                .requires("ontology"))
+            .arg(Arg::with_name("report-md")
+                .long("report-md")
+                .takes_value(true)
+                .required(false))
+            .arg(Arg::with_name("report-json")
+                .long("report-json")
+                .takes_value(true)
+                .required(false)))
         .get_matches();
 
-    let ontology_path = matches.value_of("ontology").unwrap();
-    let context = matches.value_of("context").unwrap();
-    let annotation = matches.value_of("annotation").unwrap();
+    if let Some(convert_matches) = matches.subcommand_matches("convert") {
+        convert(convert_matches);
+        return;
+    }
+
+    let inputs: Vec<&str> = matches.values_of("annotation").unwrap().collect();
+    if inputs.len() > 1 && !matches.is_present("out-dir") {
+        eprintln!("Validating several input files needs --out-dir for their outputs and reports");
+        process::exit(1);
+    }
+
+    let chunk_size = matches.value_of("threads").map(|threads| {
+        let threads: usize = threads.parse().unwrap_or_else(|_| {
+            eprintln!("--threads must be a number, but was `{}`", threads);
+            process::exit(1);
+        });
+        rayon::ThreadPoolBuilder::new().num_threads(threads).build_global().unwrap_or_else(|e| {
+            eprintln!("Could not start {} threads: {}", threads, e);
+            process::exit(1);
+        });
+        validate::DEFAULT_CHUNK_SIZE
+    });
+
+    if let Some(out_dir) = matches.value_of("out-dir") {
+        let files = resource::expand_inputs(&inputs).unwrap_or_else(|e| {
+            eprintln!("Error finding input files: {}", e);
+            process::exit(1);
+        });
+        let validation_context = load_context(&matches);
+        validate_files(&files, Path::new(out_dir), matches.value_of("name").unwrap_or("index"), chunk_size, &validation_context);
+        return;
+    }
+
     let maybe_out = matches.value_of("out");
 
-    let annotation_reader = resource::read_annotation_file(annotation).unwrap_or_else(|e| {
-        println!("Error loading annotations: {}", e);
+    let annotations = read_input(inputs[0], matches.value_of("name"));
+
+    let validation_context = load_context(&matches);
+
+    let out = match maybe_out {
+        Some(out_path) => Some(open_output(out_path, &annotations.1.header())),
+        None => None
+    };
+
+    let jsonl_out = matches.value_of("out-jsonl").map(open_jsonl);
+
+    let validator = validate::AnnotationValidator::new(annotations, &validation_context);
+    let validator = match chunk_size {
+        Some(chunk_size) => validator.parallel(chunk_size),
+        None => validator
+    };
+    let report_result = validation_annotations_into_results(validator, (out, jsonl_out));
+
+    write_reports(&matches, report_result);
+}
+
+///
+/// Validates each of the `files` with the same `context`, writing the valid annotations of each to a file of the same name
+/// in `out_dir`, along with its `<file>.report.md` and `<file>.report.json`. After all the files, `index.md` and
+/// `index.json` summarize the lines and top rules of each file. A file that can't be read is reported on stderr and left
+/// out of the index. Inputs with the same file name, like `a/mgi.gaf` and `b/mgi.gaf`, would overwrite each other's
+/// outputs, so they're an error before anything is written.
+fn validate_files(files: &[PathBuf], out_dir: &Path, name: &str, chunk_size: Option<usize>, context: &meta::Context) {
+    let duplicates = resource::duplicate_file_names(files);
+    if !duplicates.is_empty() {
+        eprintln!("More than one input file is named {}, and their outputs in {} would overwrite each other", duplicates.join(", "), out_dir.display());
+        process::exit(1);
+    }
+
+    std::fs::create_dir_all(out_dir).unwrap_or_else(|e| {
+        eprintln!("Could not make output directory {}: {}", out_dir.display(), e);
         process::exit(1);
     });
 
-    let uri_map = resource::load_prefix_context(context).unwrap_or_else(|e| {
-        println!("Error making URI prefix context: {}", e);
+    let mut aggregate = report::AggregateReport::new(name);
+    for file in files {
+        let (file_name, document, reader) = match resource::read_annotation_file(file) {
+            Ok(annotations) => annotations,
+            Err(e) => {
+                eprintln!("Skipping {}: {}", file.display(), e);
+                continue;
+            }
+        };
+
+        let out_path = out_dir.join(&file_name);
+        if out_path.canonicalize().ok() == file.canonicalize().ok() {
+            eprintln!("Skipping {}: the output would overwrite it", file.display());
+            continue;
+        }
+        let out = open_output(&out_path.to_string_lossy(), &document.header());
+
+        let validator = validate::AnnotationValidator::new((file_name.clone(), document, reader), context);
+        let validator = match chunk_size {
+            Some(chunk_size) => validator.parallel(chunk_size),
+            None => validator
+        };
+
+        let report = match validation_annotations_into_results(validator, (Some(out), None)) {
+            Ok(report) => report,
+            Err(err) => {
+                eprintln!("Error reading {}: {}", file.display(), err);
+                continue;
+            }
+        };
+
+        write_report_files(&report, &out_dir.join(format!("{}.report.md", file_name)), &out_dir.join(format!("{}.report.json", file_name)));
+        aggregate.add_report(report);
+    }
+
+    let index = aggregate.index(5);
+    write_report_files(&index, &out_dir.join("index.md"), &out_dir.join("index.json"));
+}
+
+/// Writes `report` as Markdown to `md_path` and as JSON to `json_path`, exiting if either can't be written.
+fn write_report_files<R: fmt::Display + serde::Serialize>(report: &R, md_path: &Path, json_path: &Path) {
+    let markdown = File::create(md_path).and_then(|mut f| write!(f, "{}", report));
+    if let Err(e) = markdown {
+        eprintln!("Problem Creating file at `{}`: {}", md_path.display(), e);
+        process::exit(1);
+    }
+    resource::write_json_report(report, json_path).unwrap_or_else(|e| {
+        eprintln!("Error! {}", e);
         process::exit(1);
     });
+}
 
-    let ontology_graph = resource::load_ontology(ontology_path).unwrap_or_else(|e| {
-        println!("Error building ontology: {}", e);
+///
+/// Builds the `Context` from the `context`, `ontology`, and `gpi` arguments. The ontology is left empty if it isn't given.
+fn load_context(matches: &ArgMatches) -> meta::Context {
+    let context = matches.value_of("context").unwrap();
+    let uri_map = resource::load_prefix_context(context).unwrap_or_else(|e| {
+        eprintln!("Error making URI prefix context: {}", e);
         process::exit(1);
     });
 
-    let out = match maybe_out {
-        Some(out_path) => Some(resource::write_annotation_file(out_path).unwrap_or_else(|e| {
-            println!("Could not make output at {}: {}", out_path, e);
+    let ontology_graph = match matches.value_of("ontology") {
+        Some(ontology_path) => resource::load_ontology(ontology_path).unwrap_or_else(|e| {
+            eprintln!("Error building ontology: {}", e);
             process::exit(1);
-        })),
-        None => None
+        }),
+        None => ontology::Ontology::default()
+    };
+
+    let gene_products = match matches.value_of("gpi") {
+        Some(gpi_path) => resource::load_gpi(gpi_path).unwrap_or_else(|e| {
+            eprintln!("Error loading GPI: {}", e);
+            process::exit(1);
+        }),
+        None => annotation::gpi::GeneProductTable::default()
     };
 
     let mut validation_context = meta::Context::default();
+    if let Some(gorefs_path) = matches.value_of("gorefs") {
+        let gorefs = resource::load_gorefs(gorefs_path).unwrap_or_else(|e| {
+            eprintln!("Error loading GO_REFs: {}", e);
+            process::exit(1);
+        });
+        validation_context = validation_context.add_gorefs(gorefs);
+    }
+    if let Some(today) = matches.value_of("today") {
+        let today = annotation::fields::Date::from_gaf(today).unwrap_or_else(|_| {
+            eprintln!("--today must be a date like 20210601, but was `{}`", today);
+            process::exit(1);
+        });
+        validation_context = validation_context.add_today(today.date.naive_utc());
+    }
+    if let Some(days) = matches.value_of("iea-max-age") {
+        let days: i64 = days.parse().unwrap_or_else(|_| {
+            eprintln!("--iea-max-age must be a number of days, but was `{}`", days);
+            process::exit(1);
+        });
+        validation_context = validation_context.add_iea_max_age_days(days);
+    }
+
     validation_context.uri_mapping.add_mappings(uri_map.into_iter());
-    validation_context = validation_context.add_ontology(ontology_graph);
+    validation_context.add_ontology(ontology_graph).add_gene_products(gene_products).add_rules(load_rules(matches))
+}
 
-    let report_result = validation_annotations_into_results(annotation_reader, out, validation_context);
+///
+/// Builds the `RuleRegistry` with the upstream `rule-metadata`, the `rules-config` file, and then the `disable-rule` and
+/// `rule-severity` arguments, which take precedence over the file.
+fn load_rules(matches: &ArgMatches) -> rules::RuleRegistry {
+    let mut registry = rules::RuleRegistry::default();
+    if let Some(metadata_dir) = matches.value_of("rule-metadata") {
+        let metadata = resource::load_rule_metadata(metadata_dir).unwrap_or_else(|e| {
+            eprintln!("Error loading rule metadata: {}", e);
+            process::exit(1);
+        });
+        registry.add_metadata(metadata);
+    }
+    if let Some(config_path) = matches.value_of("rules-config") {
+        let config = resource::load_rules_config(config_path).unwrap_or_else(|e| {
+            eprintln!("Error loading rules config: {}", e);
+            process::exit(1);
+        });
+        registry.configure(config).unwrap_or_else(|e| {
+            eprintln!("Error in rules config {}: {}", config_path, e);
+            process::exit(1);
+        });
+    }
 
+    for rule in matches.values_of("disable-rule").into_iter().flatten() {
+        registry.disable(rule).unwrap_or_else(|e| {
+            eprintln!("Error in --disable-rule: {}", e);
+            process::exit(1);
+        });
+    }
+
+    for rule_severity in matches.values_of("rule-severity").into_iter().flatten() {
+        let set = match rule_severity.split_once('=') {
+            Some((rule, level)) => level.parse().and_then(|severity| registry.set_severity(rule, severity)),
+            None => Err(format!("`{}` should be like gorule-0000020=error", rule_severity))
+        };
+        set.unwrap_or_else(|e| {
+            eprintln!("Error in --rule-severity: {}", e);
+            process::exit(1);
+        });
+    }
+
+    registry
+}
+
+///
+/// Opens the annotations at `path`, or reads them from stdin if `path` is `-`. The report is named `name` if given, or else
+/// after the file, or `stdin`.
+fn read_input(path: &str, name: Option<&str>) -> (String, AnnotationDocument<model::GoAssociation>, resource::AnnotationReader) {
+    let read = if path == "-" {
+        resource::read_annotations(name.unwrap_or("stdin").to_string(), io::stdin())
+    } else {
+        resource::read_annotation_file(path).map(|(file_name, document, reader)| {
+            (name.map(String::from).unwrap_or(file_name), document, reader)
+        })
+    };
+
+    read.unwrap_or_else(|e| {
+        eprintln!("Error loading annotations: {}", e);
+        process::exit(1);
+    })
+}
+
+/// Creates the annotation output at `path` with the `header`, or writes to stdout if `path` is `-`.
+fn open_output(path: &str, header: &[String]) -> resource::AnnotationWriter {
+    let write = if path == "-" {
+        resource::write_annotations(io::stdout(), header, false)
+    } else {
+        resource::write_annotation_file(path, header)
+    };
+
+    write.unwrap_or_else(|e| {
+        eprintln!("Could not make output at {}: {}", path, e);
+        process::exit(1);
+    })
+}
+
+fn open_jsonl(path: &str) -> BufWriter<Box<dyn Write>> {
+    resource::write_jsonl_file(path).unwrap_or_else(|e| {
+        eprintln!("Could not make JSON Lines output at {}: {}", path, e);
+        process::exit(1);
+    })
+}
+
+/// Writes the report to the `report-md` and `report-json` paths, if given.
+fn write_reports(matches: &ArgMatches, report_result: Result<report::Report, csv::Error>) {
     if let Some(md_path) = matches.value_of("report-md") {
         match &report_result {
             Ok(r) => {
                 let mut f = File::create(md_path).unwrap_or_else(|e| {
-                    println!("Problem Creating file at `{}`: {}", md_path, e);
+                    eprintln!("Problem Creating file at `{}`: {}", md_path, e);
                     process::exit(1);
                 });
                 let _ = write!(f, "{}", r);
             }
             Err(err) => {
-                println!("Error reading CSV: {}", err);
+                eprintln!("Error reading CSV: {}", err);
                 process::exit(1);
             }
         };
@@ -111,41 +500,152 @@ fn main() {
         match report_result {
             Ok(r) => {
                 resource::write_json_report(&r, json_path).unwrap_or_else(|e| {
-                    println!("Error! {}", e);
+                    eprintln!("Error! {}", e);
                     process::exit(1);
                 });
             }
-            Err(err) => println!("Error reading CSV: {}", err)
+            Err(err) => eprintln!("Error reading CSV: {}", err)
         };
     }
+}
+
+///
+/// The `convert` subcommand. Reads annotations in any supported format and writes them as the `to` format, along with
+/// a GPI of their gene products if `gpi-out` is given.
+fn convert(matches: &ArgMatches) {
+    let annotation = matches.value_of("annotation").unwrap();
+    let out_path = matches.value_of("out").unwrap();
+    let target = match matches.value_of("to").unwrap() {
+        "gaf-2.1" => DocumentType::Gaf(GafVersion::Gaf2_1),
+        "gaf-2.2" => DocumentType::Gaf(GafVersion::Gaf2_2),
+        "gpad-1.2" => DocumentType::Gpad(GpadVersion::Gpad1_2),
+        _ => DocumentType::Gpad(GpadVersion::Gpad2_0)
+    };
+
+    let (name, document, annotation_reader) = read_input(annotation, matches.value_of("name"));
+
+    if let DocumentType::Gpi(_) = document.document_type() {
+        eprintln!("Converting from {} is not supported", document.document_type().version_comment());
+        process::exit(1);
+    }
+
+    let context = load_context(matches);
+
+    let target_document: AnnotationDocument<model::GoAssociation> = AnnotationDocument::new(target, document.comments().to_vec());
+    let out = open_output(out_path, &target_document.header());
+
+    // GPI 2.0 goes with GPAD 2.0, and GPI 1.2 with everything else
+    let gpi_type = match target {
+        DocumentType::Gpad(GpadVersion::Gpad2_0) => DocumentType::Gpi(GpiVersion::Gpi2_0),
+        _ => DocumentType::Gpi(GpiVersion::Gpi1_2)
+    };
+    let gpi_out = matches.value_of("gpi-out").map(|gpi_path| {
+        let gpi_document: AnnotationDocument<GeneProduct> = AnnotationDocument::new(gpi_type, vec![]);
+        resource::write_annotation_file(gpi_path, &gpi_document.header()).unwrap_or_else(|e| {
+            eprintln!("Could not make GPI output at {}: {}", gpi_path, e);
+            process::exit(1);
+        })
+    });
+
+    let jsonl_out = matches.value_of("out-jsonl").map(open_jsonl);
+
+    let report_result = convert_annotations((name, annotation_reader), &document, (target, out, jsonl_out), gpi_out.map(|w| (gpi_type, w)), matches.is_present("rules"), &context);
+
+    write_reports(matches, report_result);
+}
+
+///
+/// Runs the `validator` over the annotation file. Any valid `GoAssociation` is written back out as the same document type,
+/// and as JSON to the JSON Lines writer. GPI lines have no `GoAssociation`, so GPI lines that parse are written as they are.
+fn validation_annotations_into_results(mut validator: validate::AnnotationValidator, (mut annotations_writer, mut jsonl_writer): (Option<resource::AnnotationWriter>, Option<BufWriter<Box<dyn Write>>>)) -> Result<report::Report, csv::Error> {
+    let document_type = validator.document_type();
+    let context = validator.context();
+
+    for next in &mut validator {
+        let (line_number, original, maybe_assoc, result) = next?;
+
+        if let (Some(assoc), Some(jsonl)) = (&maybe_assoc, &mut jsonl_writer) {
+            resource::write_jsonl_line(jsonl, assoc)?;
+        }
+
+        if let Some(writer) = &mut annotations_writer {
+            match maybe_assoc {
+                Some(assoc) => match RawRecord::from_association(assoc, context, document_type) {
+                    Ok(raw) => writer.serialize(raw)?,
+                    Err(err) => eprintln!("Could not write line {}: {}", line_number, err)
+                },
+                None if !result.line_skipped() && matches!(document_type, DocumentType::Gpi(_)) => writer.serialize(&original)?,
+                None => ()
+            }
+        }
+    }
 
-    
+    Ok(validator.into_report())
 }
 
-fn validation_annotations_into_results(mut annotations_reader: (String, csv::Reader<File>), mut annotations_writer: Option<csv::Writer<File>>, context: meta::Context) -> Result<report::Report, csv::Error> {
-    let deserialized = annotations_reader.1.deserialize();
-    let name = annotations_reader.0;
-    let mut report = report::Report::new(name);
+///
+/// Converts each line of the annotation file into a `GoAssociation` and writes it as the `target` document type. When
+/// `run_rules` is true the associations go through `validate::validate_record` first, so that lines with rule errors are
+/// dropped and repairs are kept. Each converted association is also written to the JSON Lines writer, if there is one,
+/// and the gene products of the associations are written once each to the `gpi_writer`.
+/// 
+/// Lines that can't be parsed or written in the target format are added to the returned report.
+fn convert_annotations(mut annotations_reader: (String, resource::AnnotationReader), document: &AnnotationDocument<model::GoAssociation>, (target, mut writer, mut jsonl_writer): (DocumentType, resource::AnnotationWriter, Option<BufWriter<Box<dyn Write>>>), mut gpi_writer: Option<(DocumentType, resource::AnnotationWriter)>, run_rules: bool, context: &meta::Context) -> Result<report::Report, csv::Error> {
+
+    let records = annotations_reader.1.records();
+    let mut report = report::Report::new(annotations_reader.0);
+    report.add_unimplemented_rules(&context.rules);
+    let mut seen_gene_products = HashSet::new();
 
-    for next in deserialized {
-        let raw: annotation::RawGaf2_1Record = match next {
-            Ok(record) => record,
-            Err(err) => { return Err(err) }
+    for next in records {
+        let record = next?;
+
+        let line_number = resource::line_number(&record);
+
+        let raw = match RawRecord::from_record(&record, document.document_type()) {
+            Ok(raw) => raw,
+            Err(err) => {
+                let mut result = rules::ResultSet::new();
+                result.add_result(rules::RuleResult::from(err));
+                report.add_result(line_number, record.iter().collect::<Vec<&str>>().join("\t"), result);
+                continue;
+            }
         };
 
-        let (maybe_assoc, next_report) = validate::parse_and_report_gaf_2_1(raw, &context, report);
-        report = next_report;
+        let (original, maybe_assoc, mut result) = if run_rules {
+            validate::validate_record(raw, context)
+        } else {
+            match raw.clone().into_association(context) {
+                Ok(assoc) => (raw, Some(assoc), rules::ResultSet::new()),
+                Err(errors) => (raw, None, rules::ResultSet::from(errors))
+            }
+        };
 
-        if let (Some(assoc), Some(writer)) = (maybe_assoc, &mut annotations_writer) {
-            let base: annotation::BaseGaf2_1Row = (assoc, &context).into();
-            let raw: annotation::RawGaf2_1Record = base.into();
-            if let Err(e) = writer.serialize(raw) {
-                return Err(e)
+        if let Some(assoc) = maybe_assoc {
+            let subject = assoc.subject.clone();
+            let assoc_json = jsonl_writer.as_ref().map(|_| assoc.clone());
+            match RawRecord::from_association(assoc, context, target) {
+                Ok(converted) => {
+                    writer.serialize(converted)?;
+                    if let (Some(jsonl), Some(assoc_json)) = (&mut jsonl_writer, &assoc_json) {
+                        resource::write_jsonl_line(jsonl, assoc_json)?;
+                    }
+                    if let Some((gpi_type, gpi)) = &mut gpi_writer {
+                        if seen_gene_products.insert(subject.id.clone()) {
+                            match GeneProduct::try_from(&subject) {
+                                Ok(product) if *gpi_type == DocumentType::Gpi(GpiVersion::Gpi2_0) => gpi.serialize(RawGpi2_0Record::from(product))?,
+                                Ok(product) => gpi.serialize(RawGpi1_2Record::from(product))?,
+                                Err(err) => result.add_result(rules::RuleResult::from(err))
+                            }
+                        }
+                    }
+                },
+                Err(err) => result.add_result(rules::RuleResult::from(err))
             }
         }
+
+        report.add_result(line_number, &original, result);
     }
 
     Ok(report)
-
 }
-
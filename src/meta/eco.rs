@@ -3,8 +3,13 @@ use std::iter::Extend;
 
 use crate::annotation::fields::Curie;
 use crate::annotation::fields::EcoCode;
+use super::goref::GoRefTable;
 
 
+///
+/// Maps GAF evidence codes to ECO classes and back. Each code has a default ECO class, and annotations made by the
+/// method of a GO_REF may have a more specific class. The GO_REF classes are built in, and are replaced by the
+/// `evidence_codes` of the GO_REF metadata when it's added with `add_gorefs`.
 pub struct EcoCodeMapping {
     eco_to_curie: HashMap<(EcoCode, Option<Curie>), Curie>,
     curie_to_eco: HashMap<Curie, EcoCode>
@@ -18,6 +23,25 @@ impl EcoCodeMapping {
         }
     }
 
+    ///
+    /// Replaces the built in GO_REF ECO classes with those of each of the `gorefs`, so that `eco_to_curie` gives that
+    /// class for the evidence code it maps to when the GO_REF is a reference. ECO classes that don't map to an evidence
+    /// code are left out. Empty `gorefs` keep the built in classes.
+    pub fn add_gorefs(&mut self, gorefs: &GoRefTable) {
+        if gorefs.is_empty() {
+            return;
+        }
+        self.eco_to_curie.retain(|(_, goref), _| goref.is_none());
+        for goref in gorefs.iter() {
+            for eco_curie in &goref.evidence_codes {
+                if let Some(code) = self.curie_to_eco(eco_curie) {
+                    self.eco_to_curie.entry((code, Some(goref.id.clone()))).or_insert_with(|| eco_curie.clone());
+                }
+            }
+        }
+    }
+
+    /// The ECO class of the evidence `eco`, which is the GO_REF's class if `goref` has one, and the default otherwise.
     pub fn eco_to_curie(&self, eco: EcoCode, goref: Option<&Curie>) -> Option<&Curie> {
         self.eco_to_curie.get(&(eco, goref.cloned()))
             .or_else(|| self.eco_to_curie.get(&(eco, None)) )
@@ -35,10 +59,14 @@ impl Default for EcoCodeMapping {
     fn default() -> EcoCodeMapping {
         let mut mapping = EcoCodeMapping::new();
         mapping.eco_to_curie.extend(default_eco_mappings::default_eco_mappings());
-        mapping.curie_to_eco.extend(default_eco_mappings::default_eco_mappings()
+        // Default classes are added last, so that a class that is both the default of one code and a GO_REF's class
+        // for another, like ECO:0000255 for ISM and for ISS with GO_REF:0000011, maps back to the code it's default for
+        let (defaults, gorefs): (Vec<_>, Vec<_>) = default_eco_mappings::default_eco_mappings()
             .into_iter()
+            .partition(|((_, goref), _)| goref.is_none());
+        mapping.curie_to_eco.extend(gorefs.into_iter().chain(defaults)
             .map(|((code, _), curie)| (curie, code)));
-        
+
         mapping
     }
 }
@@ -116,6 +144,37 @@ mod tests {
         assert_eq!(ecomap.eco_to_curie(EcoCode::IKR, None), Some(&Curie::new("ECO", "0000320")))
     }
 
+    #[test]
+    fn test_default_class_maps_back_to_its_code() {
+        let ecomap = EcoCodeMapping::default();
+        assert_eq!(ecomap.curie_to_eco(&Curie::new("ECO", "0000255")), Some(EcoCode::ISM));
+        assert_eq!(ecomap.eco_to_curie(EcoCode::ISS, Some(&Curie::new("GO_REF", "0000011"))), Some(&Curie::new("ECO", "0000255")));
+        assert_eq!(ecomap.curie_to_eco(&Curie::new("ECO", "0000031")), Some(EcoCode::ISS));
+    }
+
+    #[test]
+    fn test_goref_eco_classes() {
+        use crate::meta::goref::GoRef;
+        let interpro = Curie::new("GO_REF", "0000002");
+        let mut ecomap = EcoCodeMapping::default();
+        assert_eq!(ecomap.eco_to_curie(EcoCode::IEA, Some(&interpro)), Some(&Curie::new("ECO", "0000256")));
+        assert_eq!(ecomap.eco_to_curie(EcoCode::IEA, Some(&Curie::new("GO_REF", "0000019"))), Some(&Curie::new("ECO", "0000265")));
+        assert_eq!(ecomap.curie_to_eco(&Curie::new("ECO", "0000256")), Some(EcoCode::IEA));
+
+        let gorefs = vec![GoRef {
+            id: interpro.clone(),
+            title: "Gene Ontology annotation through association of InterPro records with GO terms".into(),
+            is_obsolete: false,
+            evidence_codes: vec![Curie::new("ECO", "0000256")]
+        }].into_iter().collect();
+        ecomap.add_gorefs(&gorefs);
+        assert_eq!(ecomap.eco_to_curie(EcoCode::IEA, Some(&interpro)), Some(&Curie::new("ECO", "0000256")));
+        // GO_REF:0000019 isn't in the loaded metadata, so it no longer has a class of its own
+        assert_eq!(ecomap.eco_to_curie(EcoCode::IEA, Some(&Curie::new("GO_REF", "0000019"))), Some(&Curie::new("ECO", "0000501")));
+        assert_eq!(ecomap.eco_to_curie(EcoCode::IEA, Some(&Curie::new("GO_REF", "0000003"))), Some(&Curie::new("ECO", "0000501")));
+        assert_eq!(ecomap.eco_to_curie(EcoCode::IEA, None), Some(&Curie::new("ECO", "0000501")));
+    }
+
     #[test]
     fn test_default_has_all_eco_codes() {
         let ecomap = EcoCodeMapping::default();
@@ -0,0 +1,65 @@
+use std::collections::HashMap;
+use std::iter::FromIterator;
+
+use serde::Deserialize;
+
+use crate::annotation::fields::Curie;
+
+///
+/// The metadata of a GO_REF, a reference to a method used to make annotations rather than to a paper. In go-site these
+/// are the YAML front matter of `metadata/gorefs/goref-XXXXXXX.md`. Other keys are ignored.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct GoRef {
+    /// Like `GO_REF:0000002`
+    pub id: Curie,
+    pub title: String,
+    #[serde(default)]
+    pub is_obsolete: bool,
+    /// The ECO classes of annotations made by the method
+    #[serde(default)]
+    pub evidence_codes: Vec<Curie>
+}
+
+/// All the `GoRef`s loaded from go-site, looked up by their `Curie`.
+#[derive(Debug, Default)]
+pub struct GoRefTable {
+    gorefs: HashMap<Curie, GoRef>
+}
+
+impl GoRefTable {
+    pub fn new() -> GoRefTable {
+        GoRefTable {
+            gorefs: HashMap::new()
+        }
+    }
+
+    pub fn insert(&mut self, goref: GoRef) {
+        self.gorefs.insert(goref.id.clone(), goref);
+    }
+
+    pub fn get(&self, id: &Curie) -> Option<&GoRef> {
+        self.gorefs.get(id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item=&GoRef> {
+        self.gorefs.values()
+    }
+
+    pub fn len(&self) -> usize {
+        self.gorefs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.gorefs.is_empty()
+    }
+}
+
+impl FromIterator<GoRef> for GoRefTable {
+    fn from_iter<I: IntoIterator<Item=GoRef>>(iter: I) -> GoRefTable {
+        let mut table = GoRefTable::new();
+        for goref in iter {
+            table.insert(goref);
+        }
+        table
+    }
+}
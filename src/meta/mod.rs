@@ -3,25 +3,46 @@
 //! that are seen in annotation data. 
 //! 
 //! Namely, the `Context` struct contains various metadata that is used to create `GoAssociation`s from different sources. 
-//! It contains the Ontology, a mapping of Labels to Uris, mapping of ECO Curies to Evidence Codes, and a Curie prefix mapping
-//! to go from Curies to Uris and back. `GoAssociation` in general cannot be made without a Context. `Context` has `default()`
-//! implemented so basic values are filled in by default.
+//! It contains the Ontology, a mapping of Labels to Uris, mapping of ECO Curies to Evidence Codes, a Curie prefix mapping
+//! to go from Curies to Uris and back, a table of gene products from a GPI file, and the `RuleRegistry` of rules to run.
+//! Rules that depend on the date compare against `today`, which can be set so runs are reproducible. The GO_REF
+//! metadata from go-site, if loaded, is used to check references and to pick the ECO class of GAF evidence codes.
+//! `GoAssociation` in general cannot be made without a Context. `Context` has `default()` implemented so basic values
+//! are filled in by default.
+//! 
+//! A `Context` is only read while validating, and is `Sync`, so one `Context` is shared by every thread in parallel
+//! validation.
 //! 
 
 pub mod curie;
 pub mod eco;
+pub mod goref;
 
 use crate::annotation::fields::*;
 
 use curie::*;
 use eco::EcoCodeMapping;
 use crate::ontology::Ontology;
+use crate::annotation::gpi::GeneProductTable;
+use goref::GoRefTable;
+use crate::rules::RuleRegistry;
+
+use chrono::{NaiveDate, Utc};
+
+/// IEA annotations older than this many days are reported by gorule-0000029
+pub const DEFAULT_IEA_MAX_AGE_DAYS: i64 = 365;
 
 pub struct Context {
     pub uri_mapping: curie::CurieMapping,
     pub label_mapping: curie::LabelMapping,
     pub eco_mapping: EcoCodeMapping,
-    pub ontology: Ontology
+    pub ontology: Ontology,
+    pub gene_products: GeneProductTable,
+    pub gorefs: GoRefTable,
+    pub rules: RuleRegistry,
+    /// The date rules treat as today, which is the current date by default
+    pub today: NaiveDate,
+    pub iea_max_age_days: i64
 }
 
 impl Context {
@@ -29,6 +50,33 @@ impl Context {
         self.ontology = ontology;
         self
     }
+
+    pub fn add_gene_products(mut self, gene_products: GeneProductTable) -> Context {
+        self.gene_products = gene_products;
+        self
+    }
+
+    /// Adds the GO_REF metadata, and the ECO classes of each GO_REF to the `eco_mapping`.
+    pub fn add_gorefs(mut self, gorefs: GoRefTable) -> Context {
+        self.eco_mapping.add_gorefs(&gorefs);
+        self.gorefs = gorefs;
+        self
+    }
+
+    pub fn add_rules(mut self, rules: RuleRegistry) -> Context {
+        self.rules = rules;
+        self
+    }
+
+    pub fn add_today(mut self, today: NaiveDate) -> Context {
+        self.today = today;
+        self
+    }
+
+    pub fn add_iea_max_age_days(mut self, days: i64) -> Context {
+        self.iea_max_age_days = days;
+        self
+    }
 }
 
 impl Default for Context {
@@ -37,7 +85,12 @@ impl Default for Context {
             uri_mapping: curie::CurieMapping::default(),
             label_mapping: curie::LabelMapping::default(),
             eco_mapping: EcoCodeMapping::default(),
-            ontology: Ontology::default()
+            ontology: Ontology::default(),
+            gene_products: GeneProductTable::default(),
+            gorefs: GoRefTable::default(),
+            rules: RuleRegistry::default(),
+            today: Utc::now().date_naive(),
+            iea_max_age_days: DEFAULT_IEA_MAX_AGE_DAYS
         }
     }
 }
@@ -54,3 +107,14 @@ impl Context {
             .and_then(|uri| self.label_mapping.uri_label(&uri).cloned())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_context_is_sync() {
+        fn shared_between_threads<T: Send + Sync>() {}
+        shared_between_threads::<Context>();
+    }
+}
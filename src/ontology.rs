@@ -193,6 +193,20 @@ impl Ontology {
         self.graph.node_references().map(|(_, node)| node).collect()
     }
 
+    /// The subsets the node `id` is in, named like `gocheck_do_not_annotate`. Subsets in obographs are URIs like
+    /// `http://purl.obolibrary.org/obo/go#gocheck_do_not_annotate`, which are named by what follows the `#`.
+    pub fn subsets(&self, id: String) -> Vec<&str> {
+        self.node(id)
+            .and_then(|node| node.meta.as_ref())
+            .map(|meta| meta.subsets.iter().map(|subset| subset.rsplit('#').next().unwrap_or(subset)).collect())
+            .unwrap_or_default()
+    }
+
+    /// Whether the node `id` is in the subset named `subset`, like `gocheck_do_not_annotate`.
+    pub fn in_subset(&self, id: String, subset: &str) -> bool {
+        self.subsets(id).contains(&subset)
+    }
+
     pub fn has_node(&self, id: String) -> bool {
         self.node_id_to_index.contains_key(&id)
     }
@@ -437,7 +451,7 @@ mod tests {
         let ontology = resource::load_ontology("resources/alt_id_ont.json").unwrap();
         let term = ontology.get_node("http://purl.obolibrary.org/obo/GO_1".into()).unwrap();
         
-        assert_eq!(term.deprecated(), true);
+        assert!(term.deprecated());
         assert_eq!(term.replaced_by(), Some("http://purl.obolibrary.org/obo/GO_2".into()));
     }
 
@@ -446,7 +460,18 @@ mod tests {
         let ontology = resource::load_ontology("resources/alt_id_ont.json").unwrap();
         let term = ontology.get_node("http://purl.obolibrary.org/obo/GO_3".into()).unwrap();
 
-        assert_eq!(term.deprecated(), true);
+        assert!(term.deprecated());
+    }
+
+    #[test]
+    fn test_subsets() {
+        let ontology = resource::load_ontology("resources/alt_id_ont.json").unwrap();
+        let binding = "http://purl.obolibrary.org/obo/GO_4".to_string();
+
+        assert_eq!(ontology.subsets(binding.clone()), vec!["gocheck_do_not_annotate", "goslim_generic"]);
+        assert!(ontology.in_subset(binding, "gocheck_do_not_annotate"));
+        assert!(!ontology.in_subset("http://purl.obolibrary.org/obo/GO_2".into(), "gocheck_do_not_annotate"));
+        assert!(ontology.subsets("http://purl.obolibrary.org/obo/GO_9999999".into()).is_empty());
     }
 
 }
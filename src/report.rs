@@ -1,4 +1,4 @@
-use crate::rules::{ResultSet, RuleResult, RuleState};
+use crate::rules::{self, ResultSet, RuleMetadata, RuleRegistry, RuleResult, RuleState};
 
 use std::fmt;
 use std::collections::HashMap;
@@ -6,7 +6,7 @@ use std::collections::HashMap;
 use serde::{Serialize};
 
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Report {
     name: String,
     minimum_level: Level,
@@ -14,6 +14,9 @@ pub struct Report {
     messages_by_rule: HashMap<String, Vec<Message>>,
     skipped: usize,
     total: usize,
+    /// Rules defined upstream in go-site that aren't implemented here, if the rule metadata was loaded
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    unimplemented_rules: Vec<RuleMetadata>
 }
 
 #[derive(Debug, PartialOrd, PartialEq, Clone, Serialize)]
@@ -38,6 +41,11 @@ impl fmt::Display for Level {
 
 #[derive(Debug, Clone, Serialize)]
 pub struct Message {
+    /// The name of the report of the input the line is in, once merged into a report of several inputs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file: Option<String>,
+    /// The physical line in the input, counting from 1 and including the `!` header lines
+    line_number: u64,
     line: String,
     message: String,
     level: Level,
@@ -56,11 +64,13 @@ impl From<RuleState> for Level {
     }
 }
 
-impl<R: fmt::Display> From<(R, RuleResult)> for Message {
-    fn from((raw, result): (R, RuleResult)) -> Message {
+impl<R: fmt::Display> From<(u64, R, RuleResult)> for Message {
+    fn from((line_number, raw, result): (u64, R, RuleResult)) -> Message {
         let line = format!("{}", raw);
         let level = Level::from(result.state());
         Message {
+            file: None,
+            line_number,
             line,
             message: result.message,
             level,
@@ -78,23 +88,170 @@ impl Report {
             minimum_level: Level::Warning,
             messages_by_rule: HashMap::new(),
             skipped: 0,
-            total: 0
+            total: 0,
+            unimplemented_rules: vec![]
         }
     }
 
-    pub fn add_result<R: fmt::Display>(&mut self, original_line: R, result: ResultSet) {
+    /// Lists the rules in the upstream metadata of `registry` that aren't implemented, at the end of the report.
+    pub fn add_unimplemented_rules(&mut self, registry: &RuleRegistry) {
+        self.unimplemented_rules = registry.unimplemented_rules().into_iter().cloned().collect();
+    }
+
+    /// Adds the `result` of validating `original_line`, which is at `line_number` in the input.
+    pub fn add_result<R: fmt::Display>(&mut self, line_number: u64, original_line: R, result: ResultSet) {
         self.total += 1;
         if result.line_skipped() {
             self.skipped += 1;
         }
-        for (rule, a_result) in result.all_results {
-            let m: Message = (&original_line, a_result).into();
-            if m.level >= self.minimum_level {
-                // insert rule -> message into vec by that rule in self.messages_by_rule
-                self.messages_by_rule.entry(rule).or_insert_with(Vec::new).push(m);
+        for (rule, rule_results) in result.all_results {
+            for a_result in rule_results {
+                let m: Message = (line_number, &original_line, a_result).into();
+                if m.level >= self.minimum_level {
+                    // insert rule -> message into vec by that rule in self.messages_by_rule
+                    self.messages_by_rule.entry(rule.clone()).or_default().push(m);
+                }
             }
         }
     }
+
+    /// Adds the lines and messages of `other` to this report, after the ones already here. The name of this report is
+    /// kept, and messages below this report's `minimum_level` are left out. If `other` has a name, its messages keep it
+    /// as the file their line is in.
+    pub fn merge(&mut self, other: Report) {
+        self.total += other.total;
+        self.skipped += other.skipped;
+        if self.unimplemented_rules.is_empty() {
+            self.unimplemented_rules = other.unimplemented_rules;
+        }
+        let file = Some(other.name).filter(|name| !name.is_empty());
+        for (rule, messages) in other.messages_by_rule {
+            let minimum_level = &self.minimum_level;
+            self.messages_by_rule.entry(rule).or_default()
+                .extend(messages.into_iter()
+                    .filter(|m| m.level >= *minimum_level)
+                    .map(|m| Message { file: m.file.or_else(|| file.clone()), ..m }));
+        }
+    }
+}
+
+///
+/// The reports of several files, like all of a group's source files. Each file's `Report` is kept, as well as a combined
+/// `Report` with the lines and messages of all of them.
+#[derive(Debug, Clone, Serialize)]
+pub struct AggregateReport {
+    name: String,
+    combined: Report,
+    reports: Vec<Report>
+}
+
+impl AggregateReport {
+    pub fn new<S: Into<String>>(name: S) -> AggregateReport {
+        let name = name.into();
+        AggregateReport {
+            combined: Report::new(name.clone()),
+            name,
+            reports: vec![]
+        }
+    }
+
+    /// Adds the `report` of one file, merging it into the combined report.
+    pub fn add_report(&mut self, report: Report) {
+        self.combined.merge(report.clone());
+        self.reports.push(report);
+    }
+
+    pub fn combined(&self) -> &Report {
+        &self.combined
+    }
+
+    pub fn reports(&self) -> &[Report] {
+        &self.reports
+    }
+
+    /// The summary of each file and of all of them combined, with the `top_rules` rules with the most messages.
+    pub fn index(&self, top_rules: usize) -> ReportIndex {
+        ReportIndex {
+            name: self.name.clone(),
+            combined: self.combined.summary(top_rules),
+            files: self.reports.iter().map(|r| r.summary(top_rules)).collect()
+        }
+    }
+}
+
+/// The number of messages for a rule in a `Report`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RuleCount {
+    pub rule: String,
+    pub messages: usize
+}
+
+/// The line counts of a `Report`, and the rules with the most messages.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportSummary {
+    pub name: String,
+    pub lines: usize,
+    pub skipped: usize,
+    pub valid: usize,
+    pub top_rules: Vec<RuleCount>
+}
+
+impl Report {
+    /// Summarizes this report, keeping the `top_rules` rules with the most messages. Ties are ordered by rule ID.
+    pub fn summary(&self, top_rules: usize) -> ReportSummary {
+        let mut counts: Vec<RuleCount> = self.messages_by_rule.iter()
+            .filter(|(_, messages)| !messages.is_empty())
+            .map(|(rule, messages)| RuleCount { rule: rule.clone(), messages: messages.len() })
+            .collect();
+        counts.sort_by(|a, b| b.messages.cmp(&a.messages).then_with(|| a.rule.cmp(&b.rule)));
+        counts.truncate(top_rules);
+
+        ReportSummary {
+            name: self.name.clone(),
+            lines: self.total,
+            skipped: self.skipped,
+            valid: self.total - self.skipped,
+            top_rules: counts
+        }
+    }
+}
+
+///
+/// The index of an `AggregateReport`, with a `ReportSummary` for each file and for all of them combined. Displayed, this
+/// is a Markdown table with a row per file.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportIndex {
+    pub name: String,
+    pub combined: ReportSummary,
+    pub files: Vec<ReportSummary>
+}
+
+impl fmt::Display for ReportIndex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn row(f: &mut fmt::Formatter<'_>, name: &str, summary: &ReportSummary) -> fmt::Result {
+            let rules: Vec<String> = summary.top_rules.iter().map(|c| format!("{} ({})", c.rule, c.messages)).collect();
+            writeln!(f, "| {} | {} | {} | {} | {} |", name, summary.lines, summary.skipped, summary.valid, rules.join(", "))
+        }
+
+        writeln!(f, "# {} Index\n", self.name)?;
+        writeln!(f, "| File | Lines | Skipped | Valid | Top Rules |")?;
+        writeln!(f, "| --- | --- | --- | --- | --- |")?;
+        for summary in &self.files {
+            row(f, &summary.name, summary)?;
+        }
+        row(f, "**All files**", &self.combined)
+    }
+}
+
+impl fmt::Display for AggregateReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "# {} Reports", self.name)?;
+        write!(f, "{}", self.combined)?;
+        for report in &self.reports {
+            write!(f, "\n{}", report)?;
+        }
+        Ok(())
+    }
 }
 
 impl Default for Report {
@@ -129,10 +286,22 @@ impl fmt::Display for Report {
                     } else {
                         "".to_string()
                     };
-                    report.push_str(&format!("* {} - Violates {}: {} {} -- `{}`\n", message.level, message.rule, message.message, entity_and_name, message.line));
+                    let location = match &message.file {
+                        Some(file) => format!("{} line {}", file, message.line_number),
+                        None => format!("line {}", message.line_number)
+                    };
+                    report.push_str(&format!("* {} - Violates {}: {} {} -- {}: `{}`\n", message.level, message.rule, message.message, entity_and_name, location, message.line));
                 }
             }
         }
+
+        if !self.unimplemented_rules.is_empty() {
+            report.push_str("### Rules not implemented\n\n");
+            for rule in &self.unimplemented_rules {
+                let id = rules::normalize_rule_id(&rule.id).unwrap_or_else(|| rule.id.clone());
+                report.push_str(&format!("* {}: {}\n", id, rule.title));
+            }
+        }
         write!(f, "{}", report)
     }
 }
@@ -153,12 +322,13 @@ mod test_report {
         
         let (_, result_set) = rules::run_rules(before_assoc, &context);
 
-        let rule_20_result = result_set.all_results.get("gorule-0000020").unwrap().to_owned();
-        let message: Message = ("`Original Annotation stand-in`".to_string(), rule_20_result).into();
+        let rule_20_result = result_set.results_for("gorule-0000020")[0].to_owned();
+        let message: Message = (12, "`Original Annotation stand-in`".to_string(), rule_20_result).into();
 
         println!("{:?}", message);
         assert_eq!(message.level, Level::Warning);
         assert_eq!(message.rule, "gorule-0000020");
+        assert_eq!(message.line_number, 12);
     }
 
     #[test]
@@ -169,10 +339,49 @@ mod test_report {
         let (_, result_set) = rules::run_rules(before_assoc, &context);
 
         let mut report = Report::default();
-        report.add_result("`Original Annotation stand-in`".to_string(), result_set);
+        report.add_result(3, "`Original Annotation stand-in`".to_string(), result_set);
 
         assert_eq!(report.total, 1);
         assert_eq!(report.messages_by_rule.get("gorule-0000020").unwrap().len(), 1);
+        assert!(report.to_string().contains("-- line 3: "));
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["messages"]["gorule-0000020"][0]["line_number"], 3);
+    }
+
+    #[test]
+    fn test_merge_reports() {
+        let before_assoc = GoAssociation::from((Subject::default(), Curie::new("BFO", "0000050"), Term::new(Curie::new("GO", "1"), None), Evidence::default(), Metadata::default(), Extensions::default()));
+        let context = Context::default().add_ontology(resource::load_ontology("resources/alt_id_ont.json").unwrap());
+        let (_, result_set) = rules::run_rules(before_assoc, &context);
+
+        let mut first = Report::new("first");
+        first.add_result(1, "line 1", result_set.clone());
+        let mut second = Report::new("second");
+        second.add_result(2, "line 2", ResultSet::new());
+        second.add_result(3, "line 3", result_set.clone());
+
+        let mut aggregate = AggregateReport::new("group");
+        aggregate.add_report(first);
+        aggregate.add_report(second);
+
+        let combined = aggregate.combined();
+        assert_eq!(combined.name, "group");
+        assert_eq!(combined.total, 3);
+        assert_eq!(combined.skipped, 0);
+        let messages = combined.messages_by_rule.get("gorule-0000020").unwrap();
+        let locations: Vec<(Option<&str>, u64)> = messages.iter().map(|m| (m.file.as_deref(), m.line_number)).collect();
+        assert_eq!(locations, vec![(Some("first"), 1), (Some("second"), 3)]);
+        assert!(combined.to_string().contains("-- second line 3: `line 3`"));
+        assert_eq!(serde_json::to_value(combined).unwrap()["messages"]["gorule-0000020"][1]["file"], "second");
+        // Each file's own report doesn't repeat its name in its messages
+        assert!(aggregate.reports()[0].to_string().contains("-- line 1: `line 1`"));
+        assert_eq!(aggregate.reports().iter().map(|r| r.total).collect::<Vec<usize>>(), vec![1, 2]);
+
+        let index = aggregate.index(5);
+        assert_eq!(index.files[1].valid, 2);
+        assert_eq!(index.combined.top_rules, vec![RuleCount { rule: "gorule-0000020".into(), messages: 2 }]);
+        assert_eq!(index.to_string().lines().nth(4), Some("| first | 1 | 0 | 1 | gorule-0000020 (1) |"));
     }
 }
 
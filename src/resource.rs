@@ -9,24 +9,50 @@
 //! `load_ontology` will load a obo-json file with a contained ontology into the `Ontology` object
 //! used in the Context.
 //! 
-//! `read_annotation_file` creates the CSV parser for the given file which will then be used to 
-//! make `GoAssociation`s.
+//! `load_gpi` will load a GPI 1.2 or GPI 2.0 file into the `GeneProductTable` used in the Context.
 //! 
-//! `write_annotation_file` creates a CSV writer to write out a parsed `GoAssociation` as an
-//! annotation.
+//! `load_rules_config` will load a YAML or JSON `RulesConfig` for the `RuleRegistry` used in the Context.
 //! 
-//! `write_json_report` takes the `Report` object and writes it out as JSON with serde.
+//! `read_annotations` reads the `!` header of any `Read` into an `AnnotationDocument` and creates the
+//! CSV parser for the rest of the input which will then be used to make `GoAssociation`s. The format of the file
+//! is detected by `detect_document_type` from the header, or from the columns of the first line.
+//! `read_annotation_file` does the same for a file path.
+//! 
+//! `write_annotations` writes a header to any `Write` and creates a CSV writer to write out a parsed `GoAssociation` as an
+//! annotation, and `write_annotation_file` does the same for a file path.
+//! 
+//! Input that starts with the gzip magic bytes is decompressed as it is read, which includes bgzip files. Output paths
+//! ending in `.gz` are gzip compressed.
+//! 
+//! `write_jsonl_file` creates a JSON Lines file, and `write_jsonl_line` writes one serialized object to it, like a
+//! `GoAssociation`.
+//! 
+//! `write_json_report` takes the `Report` object, or anything else serializable like a `ReportIndex`, and writes it out as
+//! JSON with serde.
+//! 
+//! `expand_inputs` turns the input paths given on the command line, which may be directories or glob patterns, into the
+//! list of files to read.
 //! 
 
+use serde::Serialize;
+use serde::de::DeserializeOwned;
 use serde_json::{Value};
+use std::collections::HashSet;
 use std::fs::File;
-use std::io::{BufReader};
-use std::path::Path;
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
 use std::fmt;
+use std::convert::TryFrom;
 use csv::{ReaderBuilder, WriterBuilder};
+use flate2::Compression;
+use flate2::read::MultiGzDecoder;
+use flate2::write::GzEncoder;
 
 use crate::ontology::Ontology;
-use crate::report::Report;
+use crate::meta::goref::{GoRef, GoRefTable};
+use crate::rules::{RuleMetadata, RulesConfig};
+use crate::annotation::{AnnotationDocument, DocumentType};
+use crate::annotation::gpi::{GeneProduct, GeneProductTable, RawGpi1_2Record, RawGpi2_0Record};
 
 #[derive(Debug)]
 pub enum ResourceError {
@@ -34,7 +60,10 @@ pub enum ResourceError {
     Json(serde_json::Error),
     Context(String),
     OboError(fastobo_graphs::error::Error),
-    CsvError(csv::Error)
+    CsvError(csv::Error),
+    Gpi(String),
+    Format(String),
+    Yaml(serde_yaml::Error)
 }
 
 impl fmt::Display for ResourceError {
@@ -44,7 +73,10 @@ impl fmt::Display for ResourceError {
             ResourceError::Json(err) => write!(f, "{}", err),
             ResourceError::Context(err) => write!(f, "{}", err),
             ResourceError::OboError(err) => write!(f, "{}", err),
-            ResourceError::CsvError(err) => write!(f, "{}", err)
+            ResourceError::CsvError(err) => write!(f, "{}", err),
+            ResourceError::Gpi(err) => write!(f, "{}", err),
+            ResourceError::Format(err) => write!(f, "{}", err),
+            ResourceError::Yaml(err) => write!(f, "{}", err)
         }
     }
 }
@@ -73,37 +105,391 @@ pub fn load_prefix_context<P: AsRef<Path>>(path: P) -> Result<Vec<(String, Strin
     context
 }
 
+///
+/// Loads a `RulesConfig` from `path`, which is read as JSON if it ends in `.json` and as YAML otherwise.
+pub fn load_rules_config<P: AsRef<Path>>(path: P) -> Result<RulesConfig, ResourceError> {
+    let is_json = path.as_ref().extension().map(|ext| ext == "json").unwrap_or(false);
+    let reader = File::open(path).map(BufReader::new).map_err(ResourceError::IoError)?;
+    if is_json {
+        serde_json::from_reader(reader).map_err(ResourceError::Json)
+    } else {
+        serde_yaml::from_reader(reader).map_err(ResourceError::Yaml)
+    }
+}
+
+///
+/// Parses the YAML front matter of a go-site markdown document, which is between the `---` line the document starts with
+/// and the next `---` line.
+fn parse_front_matter<T: DeserializeOwned>(document: &str) -> Result<T, ResourceError> {
+    let mut lines = document.lines();
+    if lines.next().map(str::trim_end) != Some("---") {
+        return Err(ResourceError::Format("document doesn't start with `---` front matter".into()));
+    }
+    let front_matter: Vec<&str> = lines.by_ref().take_while(|line| line.trim_end() != "---").collect();
+    serde_yaml::from_str(&front_matter.join("\n")).map_err(ResourceError::Yaml)
+}
+
+/// Parses the front matter of every markdown file in `dir` whose name starts with `prefix`, ordered by file name.
+fn load_front_matter<T: DeserializeOwned, P: AsRef<Path>>(dir: P, prefix: &str) -> Result<Vec<T>, ResourceError> {
+    let mut paths = vec![];
+    for entry in std::fs::read_dir(dir).map_err(ResourceError::IoError)? {
+        let path = entry.map_err(ResourceError::IoError)?.path();
+        let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+        if name.starts_with(prefix) && name.ends_with(".md") {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+
+    paths.iter().map(|path| {
+        let document = std::fs::read_to_string(path).map_err(ResourceError::IoError)?;
+        parse_front_matter(&document).map_err(|err| ResourceError::Format(format!("{}: {}", path.display(), err)))
+    }).collect()
+}
+
+/// Parses the `RuleMetadata` in the front matter of a go-site rule document.
+pub fn parse_rule_metadata(document: &str) -> Result<RuleMetadata, ResourceError> {
+    parse_front_matter(document)
+}
+
+///
+/// Loads the `RuleMetadata` of every `gorule-*.md` in `dir`, which is `metadata/rules` in a go-site checkout, ordered by
+/// file name.
+pub fn load_rule_metadata<P: AsRef<Path>>(dir: P) -> Result<Vec<RuleMetadata>, ResourceError> {
+    load_front_matter(dir, "gorule-")
+}
+
+/// Parses the `GoRef` in the front matter of a go-site GO_REF document.
+pub fn parse_goref(document: &str) -> Result<GoRef, ResourceError> {
+    parse_front_matter(document)
+}
+
+///
+/// Loads the GO_REF metadata at `path`, which is either a directory of `goref-*.md`, like `metadata/gorefs` in a
+/// go-site checkout, or a YAML file with a list of GO_REFs, like `metadata/gorefs.yaml`.
+pub fn load_gorefs<P: AsRef<Path>>(path: P) -> Result<GoRefTable, ResourceError> {
+    if path.as_ref().is_dir() {
+        load_front_matter::<GoRef, _>(path, "goref-").map(|gorefs| gorefs.into_iter().collect())
+    } else {
+        let reader = File::open(path).map(BufReader::new).map_err(ResourceError::IoError)?;
+        serde_yaml::from_reader::<_, Vec<GoRef>>(reader).map(|gorefs| gorefs.into_iter().collect()).map_err(ResourceError::Yaml)
+    }
+}
+
 pub fn load_ontology<P: AsRef<Path>>(path: P) -> Result<Ontology, ResourceError> {
     fastobo_graphs::from_file(path).map_err(ResourceError::OboError)
         .map(|obodoc| Ontology::from_obo_graph(&obodoc.graphs[0]))
 }
 
-pub fn read_annotation_file<P: AsRef<Path>>(path: P) -> Result<(String, csv::Reader<File>), ResourceError> {
-    let p: &Path = path.as_ref();
-    let name = p.canonicalize().unwrap().file_name().unwrap().to_str().unwrap().to_owned();
+/// Opens a GPI file with `read_gpi`.
+pub fn load_gpi<P: AsRef<Path>>(path: P) -> Result<GeneProductTable, ResourceError> {
+    let file = File::open(path).map_err(ResourceError::IoError)?;
+    read_gpi(file)
+}
 
-    ReaderBuilder::new()
+///
+/// GPI 1.2 lines have 10 columns and GPI 2.0 lines have 11, so each line is parsed according to its length. Like
+/// annotation lines, columns are only split on tabs, so a `"` is read like any other character.
+pub fn read_gpi<R: Read + 'static>(reader: R) -> Result<GeneProductTable, ResourceError> {
+    let mut reader = ReaderBuilder::new()
         .delimiter(b'\t')
+        .quoting(false)
         .flexible(true)
         .has_headers(false)
         .comment(Some(b'!'))
-        .from_path(path)
-        .map_err(ResourceError::CsvError)
-        .map(|r| (name, r))
+        .from_reader(decompress(reader).map_err(ResourceError::IoError)?);
+
+    let mut table = GeneProductTable::new();
+    for record in reader.records() {
+        let record = record.map_err(ResourceError::CsvError)?;
+        let line = record.position().map(|p| p.line()).unwrap_or(0);
+        let product = match record.len() {
+            10 => record.deserialize::<RawGpi1_2Record>(None).map_err(ResourceError::CsvError)
+                .and_then(|raw| GeneProduct::try_from(raw).map_err(|e| ResourceError::Gpi(e.to_string()))),
+            11 => record.deserialize::<RawGpi2_0Record>(None).map_err(ResourceError::CsvError)
+                .and_then(|raw| GeneProduct::try_from(raw).map_err(|e| ResourceError::Gpi(e.to_string()))),
+            n => Err(ResourceError::Gpi(format!("Expected 10 (GPI 1.2) or 11 (GPI 2.0) columns, but found {}", n)))
+        };
+        match product {
+            Ok(p) => table.insert(p),
+            Err(err) => return Err(ResourceError::Gpi(format!("Line {}: {}", line, err)))
+        }
+    }
+
+    Ok(table)
+}
+
+///
+/// Reads the `!` comment lines at the top of an annotation file, leaving `reader` at the first annotation line.
+/// The leading `!` and line ending are removed from each comment.
+pub fn read_header<R: BufRead>(reader: &mut R) -> std::io::Result<Vec<String>> {
+    let mut comments = vec![];
+    while reader.fill_buf()?.first() == Some(&b'!') {
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        comments.push(line.trim_end_matches(&['\n', '\r'][..])[1..].to_string());
+    }
+    Ok(comments)
+}
+
+///
+/// Works out the `DocumentType` of an annotation file from its header `comments` and its `first_line`.
+/// 
+/// A version comment (`gaf-version`, `gpa-version`, `gpad-version`, or `gpi-version`) in the header is used if present, and
+/// the first line must then have the required columns of that version, and at most all of its columns. Without a version
+/// comment, the type is guessed from the columns of the first line with `DocumentType::from_columns`.
+/// 
+/// Unsupported versions, or a first line that doesn't match the header, are a `ResourceError::Format`.
+pub fn detect_document_type(comments: &[String], first_line: Option<&str>) -> Result<DocumentType, ResourceError> {
+    let columns: Option<Vec<&str>> = first_line.map(|line| line.split('\t').collect());
+    let version_comment = comments.iter()
+        .find(|c| c.split(':').next().map(|key| key.trim().ends_with("-version")).unwrap_or(false));
+
+    match version_comment {
+        Some(comment) => {
+            let document_type = DocumentType::from_version_comment(comment)
+                .ok_or_else(|| ResourceError::Format(format!("Unsupported version `{}`", comment.trim())))?;
+            match columns {
+                Some(columns) if columns.len() < document_type.min_column_count() || columns.len() > document_type.column_count() => Err(ResourceError::Format(
+                    format!("Header declares `{}` with {} to {} columns, but the first line has {} columns", comment.trim(), document_type.min_column_count(), document_type.column_count(), columns.len()))),
+                _ => Ok(document_type)
+            }
+        },
+        None => match columns {
+            Some(columns) => DocumentType::from_columns(&columns)
+                .ok_or_else(|| ResourceError::Format(format!("No version in the header, and {} columns doesn't match GAF, GPAD, or GPI", columns.len()))),
+            None => Err(ResourceError::Format("No version in the header, and no lines to detect the format from".into()))
+        }
+    }
+}
+
+/// The magic bytes at the start of gzip and bgzip data.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+///
+/// Wraps `reader` so that gzip input is decompressed, and anything else is read as it is. bgzip files are a series of
+/// gzip members, so every member is read rather than only the first.
+pub fn decompress<R: Read + 'static>(reader: R) -> std::io::Result<Box<dyn Read>> {
+    let mut buf = BufReader::new(reader);
+    if buf.fill_buf()?.starts_with(&GZIP_MAGIC) {
+        Ok(Box::new(MultiGzDecoder::new(buf)))
+    } else {
+        Ok(Box::new(buf))
+    }
+}
+
+///
+/// Reads the annotation lines of an input after its header, as tab separated `StringRecord`s. `!` comments and blank
+/// lines are skipped, and the position of each record has the physical line it came from, counting from 1 and including
+/// the header, comments, and blank lines.
+/// 
+/// Lines are always read whole, so the first annotation line can be looked at with `peek` before any records are read.
+pub struct AnnotationReader {
+    reader: BufReader<Box<dyn Read>>,
+    /// Physical lines read so far
+    lines_read: u64,
+    /// An annotation line that was read by `peek`, with its line number
+    peeked: Option<(u64, String)>
+}
+
+impl AnnotationReader {
+    /// Reads annotation lines from `reader`, which is positioned after the `lines_read` lines of the header.
+    pub fn new(reader: BufReader<Box<dyn Read>>, lines_read: u64) -> AnnotationReader {
+        AnnotationReader {
+            reader,
+            lines_read,
+            peeked: None
+        }
+    }
+
+    /// The next annotation line and its line number, without its line ending, or None at the end of the input.
+    fn next_line(&mut self) -> std::io::Result<Option<(u64, String)>> {
+        if let Some(peeked) = self.peeked.take() {
+            return Ok(Some(peeked));
+        }
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if self.reader.read_line(&mut line)? == 0 {
+                return Ok(None);
+            }
+            self.lines_read += 1;
+            let line = line.trim_end_matches(&['\n', '\r'][..]);
+            if !line.is_empty() && !line.starts_with('!') {
+                return Ok(Some((self.lines_read, line.to_string())));
+            }
+        }
+    }
+
+    /// The next annotation line, without consuming it, or None at the end of the input.
+    pub fn peek(&mut self) -> std::io::Result<Option<&str>> {
+        if self.peeked.is_none() {
+            self.peeked = self.next_line()?;
+        }
+        Ok(self.peeked.as_ref().map(|(_, line)| line.as_str()))
+    }
+
+    /// Iterates over the records of the remaining annotation lines.
+    pub fn records(&mut self) -> &mut AnnotationReader {
+        self
+    }
+}
+
+impl Iterator for AnnotationReader {
+    type Item = Result<csv::StringRecord, csv::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_line() {
+            Ok(Some((line_number, line))) => {
+                let mut record = csv::StringRecord::from(line.split('\t').collect::<Vec<&str>>());
+                let mut position = csv::Position::new();
+                position.set_line(line_number);
+                record.set_position(Some(position));
+                Some(Ok(record))
+            },
+            Ok(None) => None,
+            Err(err) => Some(Err(csv::Error::from(err)))
+        }
+    }
+}
+
+/// The CSV writer for annotation lines, after the header has been written.
+pub type AnnotationWriter = csv::Writer<Box<dyn Write>>;
+
+///
+/// Reads the header of annotations from `reader` into an `AnnotationDocument`, returning it along with the given `name`
+/// and the `AnnotationReader` positioned at the first annotation line. Gzip input is decompressed.
+/// 
+/// The `DocumentType` of the input is found with `detect_document_type`, from the first annotation line read in full.
+pub fn read_annotations<R: Read + 'static, A>(name: String, reader: R) -> Result<(String, AnnotationDocument<A>, AnnotationReader), ResourceError> {
+    let mut buf = BufReader::with_capacity(64 * 1024, decompress(reader).map_err(ResourceError::IoError)?);
+    let comments = read_header(&mut buf).map_err(ResourceError::IoError)?;
+    let mut reader = AnnotationReader::new(buf, comments.len() as u64);
+    let first_line = reader.peek().map_err(ResourceError::IoError)?;
+    let document_type = detect_document_type(&comments, first_line)?;
+    let document = AnnotationDocument::new(document_type, comments);
+
+    Ok((name, document, reader))
+}
+
+/// The physical line number of `record` from an `AnnotationReader`, counting from 1 and including the header.
+pub fn line_number(record: &csv::StringRecord) -> u64 {
+    record.position().map(|p| p.line()).unwrap_or(0)
+}
+
+///
+/// Expands each of `inputs` into the files it names. A directory gives every file directly in it, except hidden ones,
+/// and a pattern with `*`, `?`, or `[` gives every file matching it as a glob. Anything else is kept as the path to a
+/// single file. The files for each input are sorted, and the inputs are kept in the order given.
+pub fn expand_inputs<S: AsRef<str>>(inputs: &[S]) -> Result<Vec<PathBuf>, ResourceError> {
+    let mut files = vec![];
+    for input in inputs {
+        let input = input.as_ref();
+        let path = Path::new(input);
+        let mut expanded: Vec<PathBuf> = if path.is_dir() {
+            std::fs::read_dir(path).map_err(ResourceError::IoError)?
+                .map(|entry| entry.map(|e| e.path()))
+                .collect::<std::io::Result<Vec<PathBuf>>>().map_err(ResourceError::IoError)?
+                .into_iter()
+                .filter(|p| p.is_file() && !p.file_name().map(|n| n.to_string_lossy().starts_with('.')).unwrap_or(false))
+                .collect()
+        } else if input.contains(&['*', '?', '['][..]) {
+            glob::glob(input).map_err(|e| ResourceError::Format(format!("Bad pattern `{}`: {}", input, e)))?
+                .filter_map(Result::ok)
+                .filter(|p| p.is_file())
+                .collect()
+        } else {
+            vec![path.to_path_buf()]
+        };
+        expanded.sort();
+        files.extend(expanded);
+    }
+    Ok(files)
+}
+
+///
+/// The file names that more than one of `files` have, like `mgi.gaf` from both `a/mgi.gaf` and `b/mgi.gaf`, in the order
+/// they're first seen. Outputs named after their input file would overwrite each other.
+pub fn duplicate_file_names(files: &[PathBuf]) -> Vec<String> {
+    let mut seen = HashSet::new();
+    let mut duplicates = vec![];
+    for name in files.iter().map(file_name) {
+        if !seen.insert(name.clone()) && !duplicates.contains(&name) {
+            duplicates.push(name);
+        }
+    }
+    duplicates
+}
+
+/// The file name of `path`, or the whole path if it has none.
+fn file_name<P: AsRef<Path>>(path: P) -> String {
+    let p: &Path = path.as_ref();
+    p.file_name()
+        .map(|file_name| file_name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| p.display().to_string())
 }
 
-pub fn write_annotation_file<P: AsRef<Path>>(path: P) -> Result<csv::Writer<File>, ResourceError> {
+///
+/// Opens an annotation file with `read_annotations`, using the file name as the name.
+pub fn read_annotation_file<P: AsRef<Path>, A>(path: P) -> Result<(String, AnnotationDocument<A>, AnnotationReader), ResourceError> {
     let p: &Path = path.as_ref();
+    let file = File::open(p).map_err(ResourceError::IoError)?;
+    read_annotations(file_name(p), file)
+}
 
-    WriterBuilder::new()
+///
+/// Writes the `header` lines to `writer` and creates a CSV writer for the annotations after it. If `gzip` is true,
+/// everything is gzip compressed.
+pub fn write_annotations<W: Write + 'static>(writer: W, header: &[String], gzip: bool) -> Result<AnnotationWriter, ResourceError> {
+    let mut out: Box<dyn Write> = if gzip {
+        Box::new(GzEncoder::new(writer, Compression::default()))
+    } else {
+        Box::new(writer)
+    };
+    for line in header {
+        writeln!(out, "{}", line).map_err(ResourceError::IoError)?;
+    }
+
+    Ok(WriterBuilder::new()
         .delimiter(b'\t')
         .quote_style(csv::QuoteStyle::Never)
         .has_headers(false)
-        .from_path(p)
-        .map_err(ResourceError::CsvError)
+        .from_writer(out))
+}
+
+/// Whether output to `path` should be gzip compressed, from its `.gz` extension.
+fn is_gzip_path(path: &Path) -> bool {
+    path.extension().map(|ext| ext == "gz").unwrap_or(false)
+}
+
+/// Creates a CSV writer for annotations at `path` with `write_annotations`, compressing if the path ends in `.gz`.
+pub fn write_annotation_file<P: AsRef<Path>>(path: P, header: &[String]) -> Result<AnnotationWriter, ResourceError> {
+    let p: &Path = path.as_ref();
+
+    let f = File::create(p).map_err(ResourceError::IoError)?;
+    write_annotations(f, header, is_gzip_path(p))
 }
 
-pub fn write_json_report<P: AsRef<Path>>(report: &Report, path: P) -> Result<(), ResourceError> {
+/// Creates a JSON Lines file at `path`, compressing if the path ends in `.gz`.
+pub fn write_jsonl_file<P: AsRef<Path>>(path: P) -> Result<BufWriter<Box<dyn Write>>, ResourceError> {
+    let p: &Path = path.as_ref();
+
+    let f = File::create(p).map_err(ResourceError::IoError)?;
+    let out: Box<dyn Write> = if is_gzip_path(p) {
+        Box::new(GzEncoder::new(f, Compression::default()))
+    } else {
+        Box::new(f)
+    };
+    Ok(BufWriter::new(out))
+}
+
+/// Writes `item` as a single line of JSON.
+pub fn write_jsonl_line<W: Write, T: Serialize>(writer: &mut W, item: &T) -> std::io::Result<()> {
+    serde_json::to_writer(&mut *writer, item)?;
+    writeln!(writer)
+}
+
+pub fn write_json_report<R: Serialize, P: AsRef<Path>>(report: &R, path: P) -> Result<(), ResourceError> {
     File::create(path).map_err(ResourceError::IoError)
         .and_then(|f: File| match serde_json::to_writer_pretty(f, report) {
             Err(err) => Err(ResourceError::Json(err)),
@@ -114,6 +500,8 @@ pub fn write_json_report<P: AsRef<Path>>(report: &Report, path: P) -> Result<(),
 #[cfg(test)]
 mod test_csv {
     use super::*;
+    use crate::annotation::{GafVersion, GpadVersion};
+    use crate::annotation::fields::{Curie, PlainString};
 
     #[test]
     fn test_csv_reader_without_comments() {
@@ -132,4 +520,140 @@ mod test_csv {
         assert_eq!(reader.records().next().unwrap().unwrap().get(0), Some("! Hello world"));
         assert_eq!(reader.records().next().unwrap().unwrap().get(1), Some("MGI:98961"))
     }
+
+    #[test]
+    fn test_read_header() {
+        let example = "!gaf-version: 2.2\r\n!generated-by: MGI\nMGI\tMGI:98961\n!trailing comment\n";
+        let mut buf = BufReader::new(example.as_bytes());
+
+        let comments = read_header(&mut buf).unwrap();
+        assert_eq!(comments, vec!["gaf-version: 2.2".to_string(), "generated-by: MGI".to_string()]);
+
+        let mut rest = String::new();
+        buf.read_line(&mut rest).unwrap();
+        assert_eq!(rest, "MGI\tMGI:98961\n");
+    }
+
+    #[test]
+    fn test_detect_document_type() {
+        let gaf = "MGI\tMGI:98961\tWnt7a\tinvolved_in\tGO:0099175\tPMID:21670302\tIMP\t\tP\t\t\tprotein\ttaxon:10090\t20180711\tSynGO\t\t";
+        let gpad = "MGI:MGI:98961\t\tRO:0002327\tGO:0003674\tPMID:21670302\tECO:0000314\t\t\t2018-07-11\tSynGO\t\t";
+
+        let header = vec!["gaf-version: 2.2".to_string()];
+        assert_eq!(detect_document_type(&header, Some(gaf)).unwrap(), DocumentType::Gaf(GafVersion::Gaf2_2));
+        assert_eq!(detect_document_type(&[], Some(gpad)).unwrap(), DocumentType::Gpad(GpadVersion::Gpad2_0));
+
+        let unsupported = vec!["gaf-version: 2.0".to_string()];
+        assert!(matches!(detect_document_type(&unsupported, Some(gaf)), Err(ResourceError::Format(_))));
+        assert!(matches!(detect_document_type(&header, Some(gpad)), Err(ResourceError::Format(_))));
+        assert!(matches!(detect_document_type(&[], Some("MGI\tMGI:98961")), Err(ResourceError::Format(_))));
+        assert!(matches!(detect_document_type(&[], None), Err(ResourceError::Format(_))));
+    }
+
+    #[test]
+    fn test_parse_rule_metadata() {
+        let document = "---\nlayout: rule\nid: GORULE:0000011\ntitle: \"ND annotations to root nodes only\"\ntype: repair\nfail_mode: hard\nstatus: approved\ncontact: \"go-quality@mailman.stanford.edu\"\nimplementations:\n  - language: python\n    source: https://github.com/biolink/ontobio/blob/master/ontobio/io/qc.py\n---\n\nThe no data (ND) evidence code should only be used for the root nodes.\n";
+        let metadata = parse_rule_metadata(document).unwrap();
+        assert_eq!(metadata.id, "GORULE:0000011");
+        assert_eq!(metadata.title, "ND annotations to root nodes only");
+        assert_eq!(metadata.status.as_deref(), Some("approved"));
+        assert_eq!(metadata.fail_mode, Some(crate::rules::FailMode::Hard));
+        assert_eq!(metadata.implementations[0].language.as_deref(), Some("python"));
+        assert!(metadata.tags.is_empty());
+
+        assert!(matches!(parse_rule_metadata("no front matter"), Err(ResourceError::Format(_))));
+    }
+
+    #[test]
+    fn test_parse_goref() {
+        let document = "---\nid: GO_REF:0000004\ntitle: Gene Ontology annotation through association of UniProtKB keywords with GO terms\nis_obsolete: true\nevidence_codes:\n  - ECO:0000501\nlayout: goref\n---\n\n## Description\n";
+        let goref = parse_goref(document).unwrap();
+        assert_eq!(goref.id, crate::annotation::fields::Curie::new("GO_REF", "0000004"));
+        assert!(goref.is_obsolete);
+        assert_eq!(goref.evidence_codes, vec![crate::annotation::fields::Curie::new("ECO", "0000501")]);
+
+        let minimal = parse_goref("---\nid: GO_REF:0000002\ntitle: InterPro\n---\n").unwrap();
+        assert!(!minimal.is_obsolete && minimal.evidence_codes.is_empty());
+    }
+
+    /// Gives at most `chunk` bytes per read, like a pipe being written to a little at a time.
+    struct TrickleReader {
+        data: Vec<u8>,
+        chunk: usize
+    }
+
+    impl Read for TrickleReader {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let n = self.chunk.min(buf.len()).min(self.data.len());
+            buf[..n].copy_from_slice(&self.data[..n]);
+            self.data.drain(..n);
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn test_detect_from_whole_first_line() {
+        let gaf = "!gaf-version: 2.2\nMGI\tMGI:98961\tWnt7a\tinvolved_in\tGO:0099175\tPMID:21670302\tIMP\t\tP\t\t\tprotein\ttaxon:10090\t20180711\tSynGO\t\t\n";
+        let trickle = TrickleReader { data: gaf.as_bytes().to_vec(), chunk: 30 };
+
+        let (_, document, mut reader) = read_annotations::<_, ()>("stdin".into(), trickle).unwrap();
+        assert_eq!(document.document_type(), DocumentType::Gaf(GafVersion::Gaf2_2));
+        assert_eq!(reader.records().next().unwrap().unwrap().len(), 17);
+    }
+
+    #[test]
+    fn test_line_numbers_count_comments_and_blank_lines() {
+        let line = "MGI\tMGI:98961\tWnt7a\tinvolved_in\tGO:0099175\tPMID:21670302\tIMP\t\tP\t\t\tprotein\ttaxon:10090\t20180711\tSynGO\t\t";
+        let gaf = format!("!gaf-version: 2.2\n!generated-by: MGI\n{}\n!mid comment\n\n{}\r\n\n{}", line, line, line);
+
+        let (_, _, reader) = read_annotations::<_, ()>("test".into(), std::io::Cursor::new(gaf)).unwrap();
+        let line_numbers: Vec<u64> = reader.map(|record| line_number(&record.unwrap())).collect();
+        assert_eq!(line_numbers, vec![3, 6, 8]);
+    }
+
+    #[test]
+    fn test_gzip_round_trip() {
+        let header = ["!gaf-version: 2.2".to_string()];
+        let line = "MGI\tMGI:98961\tWnt7a\tinvolved_in\tGO:0099175\tPMID:21670302\tIMP\t\tP\t\t\tprotein\ttaxon:10090\t20180711\tSynGO\t\t";
+
+        let mut compressed = GzEncoder::new(vec![], Compression::default());
+        writeln!(compressed, "{}\n{}", header[0], line).unwrap();
+        let bytes = compressed.finish().unwrap();
+        assert!(bytes.starts_with(&GZIP_MAGIC));
+
+        let (_, document, mut reader) = read_annotations::<_, ()>("test".into(), std::io::Cursor::new(bytes)).unwrap();
+        assert_eq!(document.document_type(), DocumentType::Gaf(GafVersion::Gaf2_2));
+        assert_eq!(reader.records().next().unwrap().unwrap().get(3), Some("involved_in"));
+
+        let mut plain = String::new();
+        decompress(std::io::Cursor::new(line.as_bytes().to_vec())).unwrap().read_to_string(&mut plain).unwrap();
+        assert_eq!(plain, line);
+    }
+
+    #[test]
+    fn test_read_gpi_with_quotes() {
+        let gpi = "!gpi-version: 1.2\nMGI\tMGI:98961\tWnt7a\t\"wingless-type\" MMTV integration site family, member 7A\tWnt-7a|\"Wnt7a\"\tprotein\ttaxon:10090\t\t\t\n";
+        let table = read_gpi(std::io::Cursor::new(gpi.as_bytes().to_vec())).unwrap();
+        let product = table.get(&Curie::new("MGI", "MGI:98961")).unwrap();
+        assert_eq!(product.name, Some(PlainString("\"wingless-type\" MMTV integration site family, member 7A".into())));
+        assert_eq!(product.synonyms.items().len(), 2);
+    }
+
+    #[test]
+    fn test_expand_inputs() {
+        // resources/inputs has a.gaf, b.gpad, a hidden file, and a directory b/ with another a.gaf
+        let a = PathBuf::from("resources/inputs/a.gaf");
+        let b = PathBuf::from("resources/inputs/b.gpad");
+        assert_eq!(expand_inputs(&["resources/inputs"]).unwrap(), vec![a.clone(), b.clone()]);
+        assert_eq!(expand_inputs(&["resources/inputs/*.gpad", "Cargo.toml"]).unwrap(), vec![b, PathBuf::from("Cargo.toml")]);
+        assert!(expand_inputs(&["resources/inputs/*.gpi"]).unwrap().is_empty());
+        assert_eq!(expand_inputs(&["resources/inputs/b"]).unwrap(), vec![PathBuf::from("resources/inputs/b/a.gaf")]);
+    }
+
+    #[test]
+    fn test_duplicate_file_names() {
+        let files = expand_inputs(&["resources/inputs", "resources/inputs/b"]).unwrap();
+        assert_eq!(duplicate_file_names(&files), vec!["a.gaf".to_string()]);
+        assert!(duplicate_file_names(&expand_inputs(&["resources/inputs"]).unwrap()).is_empty());
+    }
 }
@@ -1,10 +1,12 @@
 use crate::annotation::model::{GoAssociation};
 use crate::meta::Context;
-use crate::ontology::{NodeDeprecated};
+use crate::ontology::{NodeAspect, NodeDeprecated};
 use crate::annotation::fields::*;
 
 use std::collections::HashMap;
 
+use serde::{Deserialize, Serialize};
+
 
 
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
@@ -20,7 +22,7 @@ pub enum RuleState {
 pub struct RuleResult {
     /// This is the id of the GO Rule: like gorule-0000001
     pub rule: String,
-    /// False if the rule failed, true if it passed
+    /// False if the rule failed, true if it passed. A repaired association still failed the rule.
     pub valid: bool,
     /// This is a message about the rule failure, typically just the rule title
     pub message: String,
@@ -53,13 +55,14 @@ impl RuleResult {
     }
 }
 
-/// This contains a map from rule IDs (like `gorule-0000001`) to the RuleResult of running that rule
-/// on a GoAssociation
-#[derive(Debug)]
+/// This contains a map from rule IDs (like `gorule-0000001`) to the RuleResults of running that rule
+/// on a GoAssociation. Most rules give one result per line, but gorule-0000001 gives one for each column
+/// that failed to parse.
+#[derive(Debug, Clone)]
 pub struct ResultSet {
-    /// A map from Rule ID to RuleResult, which internal association
+    /// A map from Rule ID to RuleResults, which internal association
     /// in the map point at this ResultSet association
-    pub all_results: HashMap<String, RuleResult>
+    pub all_results: HashMap<String, Vec<RuleResult>>
 }
 
 impl ResultSet {
@@ -69,29 +72,37 @@ impl ResultSet {
         }
     }
 
-    /// Add a single result. The given `result`'s rule field will be used to insert into the `all_results` map.
+    /// Add a single result. The given `result`'s rule field will be used to insert into the `all_results` map,
+    /// after any results already there for that rule.
     pub fn add_result(&mut self, result: RuleResult) {
-        self.all_results.insert(result.rule.clone(), result);
+        self.all_results.entry(result.rule.clone()).or_default().push(result);
     }
 
     /// Add an many results. Takes a type that implements `IntoIterator` where the Item type
     /// is a tuple of (String, RuleResult), where the first element is the rule ID.
     pub fn add_results<It: IntoIterator<Item=(String, RuleResult)>>(&mut self, results: It) {
-        self.all_results.extend(results.into_iter())
+        for (rule, result) in results {
+            self.all_results.entry(rule).or_default().push(result);
+        }
+    }
+
+    /// The results for `rule`, or an empty slice if there are none.
+    pub fn results_for(&self, rule: &str) -> &[RuleResult] {
+        self.all_results.get(rule).map(Vec::as_slice).unwrap_or(&[])
     }
 
     /// Since a line is skipped/filtered if it violates a rule as `RuleState::Error`, this will check
     /// if there are any `RuleResult`s as values that have RuleState::Error. True if if there are any,
     /// false if there are none.
     pub fn line_skipped(&self) -> bool {
-        self.all_results.values().any(|r| r.state == RuleState::Error)
+        self.all_results.values().flatten().any(|r| r.state == RuleState::Error)
     }
 
     /// Gets the worst RuleState seen in this set of results.
     /// Goes Ok -> Warning -> Repair -> Error
     pub fn worst_level_state(&self) -> Option<RuleState> {
         let mut worst = None;
-        for r in self.all_results.values() {
+        for r in self.all_results.values().flatten() {
             if worst.is_none() || r.state > worst.unwrap() {
                 worst = Some(r.state)
             }
@@ -134,7 +145,8 @@ pub enum RuleTagResult {
 /// Rules are actually run with the `validate` function, but this is implemented by default by calling `rule_impl` and handling
 /// the boilerplate of the full, final RuleResult paired with the corresponding GoAssociation. The `id` and `description` are used
 /// indirectly through the default implementation of `meta()` yielding a `RuleMeta` type continaing everyhing `validate` needs to 
-/// fully make a `RuleResult`.
+/// fully make a `RuleResult`. Only `Pass` is `valid`. `Error` gives `RuleState::Error`, which drops the line, and keeps the
+/// association as it was before the rule.
 /// 
 /// Example:
 /// 
@@ -155,20 +167,21 @@ pub enum RuleTagResult {
 /// }
 /// ```
 ///
-pub trait Rule {
+pub trait Rule: Send + Sync {
     fn validate(&self, association: GoAssociation, context: &Context) -> (GoAssociation, RuleResult) {
+        let meta = self.meta();
         match self.rule_impl(association.clone(), context) {
             RuleTagResult::Pass(assoc) => {
-                (assoc, RuleResult::new(self.meta().rule_id, self.meta().description, "".into(), "".into(), true, RuleState::Ok))
+                (assoc, RuleResult::new(meta.rule_id, meta.description, "".into(), "".into(), true, RuleState::Ok))
             },
             RuleTagResult::Warning(assoc, name, offending) => {
-                (assoc, RuleResult::new(self.meta().rule_id, self.meta().description, offending, name, true, RuleState::Warning))
+                (assoc, RuleResult::new(meta.rule_id, meta.description, offending, name, false, RuleState::Warning))
             },
             RuleTagResult::Repair(assoc, name, offending) => {
-                (assoc, RuleResult::new(self.meta().rule_id, self.meta().description, offending, name, true, RuleState::Repaired))
+                (assoc, RuleResult::new(meta.rule_id, meta.description, offending, name, false, RuleState::Repaired))
             },
             RuleTagResult::Error(name, offending) => {
-                (association, RuleResult::new(self.meta().rule_id, self.meta().description, offending, name, true, RuleState::Warning))
+                (association, RuleResult::new(meta.rule_id, meta.description, offending, name, false, RuleState::Error))
             }
         }
     }
@@ -230,7 +243,167 @@ impl Rule for Rule11 {
     }
 }
 
-#[derive(Debug, Clone)]
+///
+/// Terms in the `gocheck_do_not_annotate` subset must not be used, and terms in `gocheck_do_not_manually_annotate` may
+/// only be used by IEA annotations.
+#[derive(Debug, Default, Clone)]
+struct Rule08;
+
+impl Rule for Rule08 {
+    fn description(&self) -> &'static str {"No annotations should be made to uninformative high level terms"}
+
+    fn id(&self) -> u32 {8}
+
+    fn rule_impl(&self, association: GoAssociation, context: &Context) -> RuleTagResult {
+        let in_subset = |subset: &str| context.uri_mapping.uri_for_curie(&association.object.id)
+            .map(|uri| context.ontology.in_subset(uri, subset))
+            .unwrap_or(false);
+        let do_not_annotate = in_subset("gocheck_do_not_annotate");
+        let manual = evidence_code(&association, context) != Some(EcoCode::IEA);
+        if do_not_annotate || (manual && in_subset("gocheck_do_not_manually_annotate")) {
+            let goterm = association.object.id.to_string();
+            RuleTagResult::Warning(association, "GO term".into(), goterm)
+        } else {
+            RuleTagResult::Pass(association)
+        }
+    }
+}
+
+///
+/// The declared aspect of an annotation, like GAF column 9, must match the namespace of its GO term, and is repaired to
+/// match when it doesn't. Annotations without a declared aspect, like from GPAD, have nothing to check, and neither do
+/// terms missing from the ontology, which Rule20 reports. A term in the ontology without a namespace is an error.
+#[derive(Debug, Default, Clone)]
+struct Rule28;
+
+impl Rule for Rule28 {
+    fn description(&self) -> &'static str {"Aspect can only be one of C, P, F"}
+
+    fn id(&self) -> u32 {28}
+
+    fn rule_impl(&self, mut association: GoAssociation, context: &Context) -> RuleTagResult {
+        let declared = match association.aspect {
+            Some(aspect) => aspect,
+            None => return RuleTagResult::Pass(association)
+        };
+
+        let node = context.uri_mapping.uri_for_curie(&association.object.id)
+            .and_then(|uri| context.ontology.node(uri));
+        let node = match node {
+            Some(node) => node,
+            None => return RuleTagResult::Pass(association)
+        };
+        match node.aspect() {
+            Some(aspect) if aspect == declared => RuleTagResult::Pass(association),
+            Some(aspect) => {
+                association.aspect = Some(aspect);
+                RuleTagResult::Repair(association, "Aspect repaired".into(), aspect.to_string())
+            },
+            None => RuleTagResult::Error("GO term has no namespace".into(), association.object.id.to_string())
+        }
+    }
+}
+
+///
+/// IEA annotations are reported when they are older than the `Context`'s `iea_max_age_days`, counting back from its
+/// `today`.
+#[derive(Debug, Default, Clone)]
+struct Rule29;
+
+impl Rule for Rule29 {
+    fn description(&self) -> &'static str {"All IEAs over a year old are removed"}
+
+    fn id(&self) -> u32 {29}
+
+    fn rule_impl(&self, association: GoAssociation, context: &Context) -> RuleTagResult {
+        let age = context.today.signed_duration_since(association.date.date.naive_utc());
+        if evidence_code(&association, context) == Some(EcoCode::IEA) && age.num_days() > context.iea_max_age_days {
+            let date = association.date.to_string();
+            RuleTagResult::Warning(association, "Date".into(), date)
+        } else {
+            RuleTagResult::Pass(association)
+        }
+    }
+}
+
+///
+/// GO_REF references must be to GO_REFs in the `Context`'s GO_REF metadata that aren't obsolete. Without GO_REF
+/// metadata there's nothing to check against, so the rule passes.
+#[derive(Debug, Default, Clone)]
+struct Rule30;
+
+impl Rule for Rule30 {
+    fn description(&self) -> &'static str {"Deprecated GO_REFs are not allowed"}
+
+    fn id(&self) -> u32 {30}
+
+    fn rule_impl(&self, association: GoAssociation, context: &Context) -> RuleTagResult {
+        if context.gorefs.is_empty() {
+            return RuleTagResult::Pass(association);
+        }
+
+        let invalid: Vec<String> = association.evidence.has_supporting_reference.items().iter()
+            .filter(|reference| reference.same_namespace("GO_REF"))
+            .filter(|reference| context.gorefs.get(reference).map(|goref| goref.is_obsolete).unwrap_or(true))
+            .map(|reference| reference.to_string())
+            .collect();
+        if invalid.is_empty() {
+            RuleTagResult::Pass(association)
+        } else {
+            RuleTagResult::Warning(association, "Reference".into(), invalid.join("|"))
+        }
+    }
+}
+
+/// The GAF evidence code of the association's ECO evidence, if it has one.
+fn evidence_code(association: &GoAssociation, context: &Context) -> Option<EcoCode> {
+    context.eco_mapping.curie_to_eco(&association.evidence.id)
+}
+
+/// Every CURIE in the association's with/from, across all of its conjunctions.
+fn with_from_curies(association: &GoAssociation) -> impl Iterator<Item=&Curie> {
+    association.evidence.with_support_from.items().iter().flat_map(|conjunction| conjunction.elements())
+}
+
+///
+/// IC annotations are inferred from another GO term, so their with/from must have at least one GO term in it.
+#[derive(Debug, Default, Clone)]
+struct Rule16;
+
+impl Rule for Rule16 {
+    fn description(&self) -> &'static str { "IC annotations require a With/From entry" }
+
+    fn id(&self) -> u32 {16}
+
+    fn rule_impl(&self, association: GoAssociation, context: &Context) -> RuleTagResult {
+        if evidence_code(&association, context) == Some(EcoCode::IC) && !with_from_curies(&association).any(|curie| curie.same_namespace("GO")) {
+            let evidence = association.evidence.id.to_string();
+            RuleTagResult::Warning(association, "Evidence".into(), evidence)
+        } else {
+            RuleTagResult::Pass(association)
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+struct Rule17;
+
+impl Rule for Rule17 {
+    fn description(&self) -> &'static str { "IDA annotations must not have a With/From entry" }
+
+    fn id(&self) -> u32 {17}
+
+    fn rule_impl(&self, association: GoAssociation, context: &Context) -> RuleTagResult {
+        if evidence_code(&association, context) == Some(EcoCode::IDA) && !association.evidence.with_support_from.items().is_empty() {
+            let with_from = association.evidence.with_support_from.to_string();
+            RuleTagResult::Warning(association, "with/from".into(), with_from)
+        } else {
+            RuleTagResult::Pass(association)
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone)]
 struct Rule18;
 
 impl Rule for Rule18 {
@@ -238,21 +411,45 @@ impl Rule for Rule18 {
 
     fn id(&self) -> u32 {18}
 
-    fn rule_impl(&self, association: GoAssociation, _: &Context) -> RuleTagResult {
-        let ipi = Curie::new("ECO", "0000353");
-        if association.evidence.id == ipi {
-            // If evidence is IPI, then we should expect a withfrom entry
-            if association.evidence.with_support_from.items().is_empty() {
-                RuleTagResult::Pass(association)
-            } else {
-                RuleTagResult::Warning(association, "with/from".into(), "Empty".into())
-            }
+    fn rule_impl(&self, association: GoAssociation, context: &Context) -> RuleTagResult {
+        if evidence_code(&association, context) == Some(EcoCode::IPI) && association.evidence.with_support_from.items().is_empty() {
+            let evidence = association.evidence.id.to_string();
+            RuleTagResult::Warning(association, "Evidence".into(), evidence)
         } else {
             RuleTagResult::Pass(association)
         }
     }
 }
 
+///
+/// The with/from of ISS, ISO, ISA, and ISM annotations must be gene products, which are looked up in the `Context`'s
+/// GPI. Without a GPI there's nothing to check against, so the rule passes.
+#[derive(Debug, Default, Clone)]
+struct Rule50;
+
+impl Rule for Rule50 {
+    fn description(&self) -> &'static str { "ISS, ISO, ISA, and ISM annotations must have gene products in With/From" }
+
+    fn id(&self) -> u32 {50}
+
+    fn rule_impl(&self, association: GoAssociation, context: &Context) -> RuleTagResult {
+        let sequence_similarity = matches!(evidence_code(&association, context), Some(EcoCode::ISS | EcoCode::ISO | EcoCode::ISA | EcoCode::ISM));
+        if !sequence_similarity || context.gene_products.is_empty() {
+            return RuleTagResult::Pass(association);
+        }
+
+        let not_gene_products: Vec<String> = with_from_curies(&association)
+            .filter(|curie| context.gene_products.get(curie).is_none())
+            .map(|curie| curie.to_string())
+            .collect();
+        if not_gene_products.is_empty() {
+            RuleTagResult::Pass(association)
+        } else {
+            RuleTagResult::Warning(association, "with/from".into(), not_gene_products.join("|"))
+        }
+    }
+}
+
 #[derive(Debug, Default, Clone)]
 struct Rule20;
 
@@ -294,21 +491,284 @@ fn rules() -> Vec<Box<dyn Rule>> {
     vec![
         Box::new(Rule02),
         Box::new(Rule11::default()),
-        Box::new(Rule20)
+        Box::new(Rule16),
+        Box::new(Rule17),
+        Box::new(Rule18),
+        Box::new(Rule20),
+        // After Rule20, so that the subsets checked are those of the term an alt_id was repaired to
+        Box::new(Rule08),
+        Box::new(Rule28),
+        Box::new(Rule29),
+        Box::new(Rule30),
+        Box::new(Rule50)
     ]
 }
 
-pub fn run_rules(association: GoAssociation, context: &Context) -> (GoAssociation, ResultSet) {
-    let mut results: Vec<(String, RuleResult)> = vec![];
-    let mut current_association = association;
-    for rule in rules() {
-        let (validated_assoc, result) = rule.validate(current_association, context);
-        current_association = validated_assoc;
-        results.push((result.rule.clone(), result));
+/// Turns a rule ID written as `gorule-0000002`, `GORULE:0000002` (as in go-site), `gorule-2`, or `2` into the full
+/// `gorule-0000002` form.
+pub fn normalize_rule_id(id: &str) -> Option<String> {
+    let id = id.trim().to_lowercase();
+    let number = id.trim_start_matches("gorule-").trim_start_matches("gorule:");
+    number.parse::<u32>().ok().map(|n| format!("gorule-{:0width$}", n, width=7))
+}
+
+/// The severity a rule is reported at when it doesn't pass, overriding the rule's own.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Warning,
+    Error
+}
+
+impl std::str::FromStr for Severity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Severity, String> {
+        match s.to_lowercase().as_str() {
+            "warning" => Ok(Severity::Warning),
+            "error" => Ok(Severity::Error),
+            _ => Err(format!("`{}` is not a severity, use `warning` or `error`", s))
+        }
+    }
+}
+
+impl From<Severity> for RuleState {
+    fn from(severity: Severity) -> RuleState {
+        match severity {
+            Severity::Warning => RuleState::Warning,
+            Severity::Error => RuleState::Error
+        }
+    }
+}
+
+/// How a rule fails upstream: `soft` is reported as a warning, and `hard` as an error.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FailMode {
+    Soft,
+    Hard
+}
+
+impl From<FailMode> for Severity {
+    fn from(fail_mode: FailMode) -> Severity {
+        match fail_mode {
+            FailMode::Soft => Severity::Warning,
+            FailMode::Hard => Severity::Error
+        }
+    }
+}
+
+/// An implementation of a rule listed in its go-site metadata, like the SPARQL query that checks it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Implementation {
+    pub language: Option<String>,
+    pub source: Option<String>,
+    pub code: Option<String>
+}
+
+///
+/// The metadata of a rule from the YAML front matter of its `gorule-XXXXXXX.md` in go-site
+/// (`github.com/geneontology/go-site/tree/master/metadata/rules`), which is the source of truth for the rule's title and
+/// how it fails. Other keys in the front matter are ignored.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RuleMetadata {
+    /// Written like `GORULE:0000002` upstream
+    pub id: String,
+    pub title: String,
+    #[serde(default)]
+    pub status: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub fail_mode: Option<FailMode>,
+    #[serde(default)]
+    pub implementations: Vec<Implementation>
+}
+
+/// The configuration of one rule. Anything left out is left as the rule's default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RuleConfig {
+    /// False to not run the rule
+    pub enabled: Option<bool>,
+    /// The severity to report the rule at when it doesn't pass
+    pub severity: Option<Severity>
+}
+
+impl RuleConfig {
+    /// This config, with anything set in `other` taking its place.
+    fn overridden_by(self, other: RuleConfig) -> RuleConfig {
+        RuleConfig {
+            enabled: other.enabled.or(self.enabled),
+            severity: other.severity.or(self.severity)
+        }
+    }
+}
+
+///
+/// The configuration of a `RuleRegistry`, read from YAML or JSON like:
+/// 
+/// ```yaml
+/// rules:
+///   gorule-0000002:
+///     enabled: false
+///   gorule-0000020:
+///     severity: error
+/// groups:
+///   MGI:
+///     gorule-0000020:
+///       severity: warning
+/// ```
+/// 
+/// `rules` applies to every annotation, and `groups` overrides it for the annotations with that `provided_by`.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RulesConfig {
+    #[serde(default)]
+    pub rules: HashMap<String, RuleConfig>,
+    #[serde(default)]
+    pub groups: HashMap<String, HashMap<String, RuleConfig>>
+}
+
+///
+/// The rules to run on each `GoAssociation`, with the `RulesConfig` saying which are enabled and how severe they are.
+/// The default registry has every rule enabled at its own severity.
+/// 
+/// With `add_metadata`, the title of each rule in the upstream `RuleMetadata` is used as its message, and its `fail_mode`
+/// is the default severity of warnings and errors, unless the `RulesConfig` says otherwise.
+pub struct RuleRegistry {
+    rules: Vec<Box<dyn Rule>>,
+    config: RulesConfig,
+    /// Upstream metadata by full rule ID, like `gorule-0000002`
+    metadata: HashMap<String, RuleMetadata>
+}
+
+impl RuleRegistry {
+    pub fn new(rules: Vec<Box<dyn Rule>>) -> RuleRegistry {
+        RuleRegistry {
+            rules,
+            config: RulesConfig::default(),
+            metadata: HashMap::new()
+        }
+    }
+
+    /// Adds the upstream `metadata` of rules, whether or not they are implemented here. Metadata with an ID that isn't a
+    /// rule ID is left out.
+    pub fn add_metadata<I: IntoIterator<Item=RuleMetadata>>(&mut self, metadata: I) {
+        for rule_metadata in metadata {
+            if let Some(id) = normalize_rule_id(&rule_metadata.id) {
+                self.metadata.insert(id, rule_metadata);
+            }
+        }
+    }
+
+    ///
+    /// The upstream metadata of rules that aren't implemented here, ordered by ID. Deprecated rules are left out, since
+    /// they aren't meant to be implemented.
+    pub fn unimplemented_rules(&self) -> Vec<&RuleMetadata> {
+        let implemented = self.rule_ids();
+        let mut unimplemented: Vec<(&String, &RuleMetadata)> = self.metadata.iter()
+            .filter(|(id, metadata)| !implemented.contains(id) && metadata.status.as_deref() != Some("deprecated"))
+            .collect();
+        unimplemented.sort_by_key(|(id, _)| id.as_str());
+        unimplemented.into_iter().map(|(_, metadata)| metadata).collect()
+    }
+
+    /// The IDs of the rules in this registry, like `gorule-0000002`.
+    pub fn rule_ids(&self) -> Vec<String> {
+        self.rules.iter().map(|r| r.meta().rule_id).collect()
+    }
+
+    ///
+    /// Sets the `config` of this registry. Rule IDs in the config may be written as in `normalize_rule_id`. It is an
+    /// Err if the config names a rule that isn't in the registry.
+    pub fn configure(&mut self, config: RulesConfig) -> Result<(), String> {
+        let mut normalized = RulesConfig::default();
+        for (id, rule_config) in config.rules {
+            normalized.rules.insert(self.known_rule_id(&id)?, rule_config);
+        }
+        for (group, group_rules) in config.groups {
+            let mut normalized_group = HashMap::new();
+            for (id, rule_config) in group_rules {
+                normalized_group.insert(self.known_rule_id(&id)?, rule_config);
+            }
+            normalized.groups.insert(group, normalized_group);
+        }
+        self.config = normalized;
+        Ok(())
+    }
+
+    /// Stops the rule `id` from running.
+    pub fn disable(&mut self, id: &str) -> Result<(), String> {
+        let id = self.known_rule_id(id)?;
+        self.config.rules.entry(id).or_default().enabled = Some(false);
+        Ok(())
+    }
+
+    /// Reports the rule `id` at `severity` when it doesn't pass.
+    pub fn set_severity(&mut self, id: &str, severity: Severity) -> Result<(), String> {
+        let id = self.known_rule_id(id)?;
+        self.config.rules.entry(id).or_default().severity = Some(severity);
+        Ok(())
+    }
+
+    fn known_rule_id(&self, id: &str) -> Result<String, String> {
+        match normalize_rule_id(id) {
+            Some(full_id) if self.rule_ids().contains(&full_id) => Ok(full_id),
+            _ => Err(format!("`{}` is not one of the rules: {}", id, self.rule_ids().join(", ")))
+        }
     }
-    let mut result_set = ResultSet::new();
-    result_set.add_results(results);
-    (current_association, result_set)
+
+    /// The config for the rule `id` on annotations from `provided_by`.
+    fn rule_config(&self, id: &str, provided_by: &str) -> RuleConfig {
+        let config = self.config.rules.get(id).copied().unwrap_or_default();
+        match self.config.groups.get(provided_by).and_then(|group| group.get(id)) {
+            Some(group_config) => config.overridden_by(*group_config),
+            None => config
+        }
+    }
+
+    ///
+    /// Runs each enabled rule on the `association` in order, with each rule getting the association from the rule
+    /// before it. A rule that doesn't pass is reported at the configured severity, if there is one. Otherwise warnings
+    /// and errors are reported at the severity of the rule's upstream `fail_mode`, and repairs are left as repairs.
+    pub fn run(&self, association: GoAssociation, context: &Context) -> (GoAssociation, ResultSet) {
+        let mut result_set = ResultSet::new();
+        let mut current_association = association;
+        for rule in &self.rules {
+            let id = rule.meta().rule_id;
+            let config = self.rule_config(&id, &current_association.provided_by.value);
+            if config.enabled == Some(false) {
+                continue;
+            }
+
+            let (validated_assoc, mut result) = rule.validate(current_association, context);
+            let metadata = self.metadata.get(&id);
+            if let Some(metadata) = metadata {
+                result.message = metadata.title.clone();
+            }
+            let default_severity = metadata.and_then(|m| m.fail_mode).map(Severity::from);
+            match (config.severity, default_severity) {
+                (Some(severity), _) if result.state != RuleState::Ok => result.state = severity.into(),
+                (None, Some(severity)) if matches!(result.state, RuleState::Warning | RuleState::Error) => result.state = severity.into(),
+                _ => ()
+            }
+            current_association = validated_assoc;
+            result_set.add_result(result);
+        }
+        (current_association, result_set)
+    }
+}
+
+impl Default for RuleRegistry {
+    fn default() -> RuleRegistry {
+        RuleRegistry::new(rules())
+    }
+}
+
+/// Runs the rules of the `context`'s `RuleRegistry` on the `association`.
+pub fn run_rules(association: GoAssociation, context: &Context) -> (GoAssociation, ResultSet) {
+    context.rules.run(association, context)
 }
 
 #[cfg(test)]
@@ -317,6 +777,244 @@ mod test_rules {
     use crate::annotation::model::*;
     use crate::resource;
 
+    fn association(term: Curie, evidence: Curie) -> GoAssociation {
+        let mut association = GoAssociation::from((Subject::default(), Curie::new("BFO", "0000050"), Term::new(term, None), Evidence::default(), Metadata::default(), Extensions::default()));
+        association.evidence.id = evidence;
+        association
+    }
+
+    fn ontology_context() -> Context {
+        Context::default().add_ontology(resource::load_ontology("resources/alt_id_ont.json").unwrap())
+    }
+
+    /// The state and validity of running `rule` on `association`
+    fn check<R: Rule>(rule: &R, association: GoAssociation, context: &Context) -> (RuleState, bool) {
+        let (_, result) = rule.validate(association, context);
+        (result.state, result.valid)
+    }
+
+    fn with_from(curies: &[Curie]) -> ListField<Conjunction<Curie>> {
+        ListField::new(curies.iter().map(|curie| Conjunction::new(vec![curie.clone()])).collect::<Vec<_>>())
+    }
+
+    /// The state of `rule` from running all the rules on `association`
+    fn run_state(rule: &str, association: GoAssociation, context: &Context) -> RuleState {
+        let (_, result_set) = run_rules(association, context);
+        result_set.results_for(rule)[0].state
+    }
+
+    #[test]
+    fn test_rule_08_do_not_annotate_subsets() {
+        let context = ontology_context();
+        let ida = Curie::new("ECO", "0000314");
+        let iea = Curie::new("ECO", "0000501");
+        // GO:4 is in gocheck_do_not_annotate, and GO:5 in gocheck_do_not_manually_annotate
+        assert_eq!(run_state("gorule-0000008", association(Curie::new("GO", "2"), ida.clone()), &context), RuleState::Ok);
+        assert_eq!(run_state("gorule-0000008", association(Curie::new("GO", "4"), ida.clone()), &context), RuleState::Warning);
+        assert_eq!(run_state("gorule-0000008", association(Curie::new("GO", "4"), iea.clone()), &context), RuleState::Warning);
+        assert_eq!(run_state("gorule-0000008", association(Curie::new("GO", "5"), ida.clone()), &context), RuleState::Warning);
+        assert_eq!(run_state("gorule-0000008", association(Curie::new("GO", "5"), iea), &context), RuleState::Ok);
+
+        // GO:6 is replaced by GO:4, so it's checked after Rule20 repairs it
+        let (assoc, result_set) = run_rules(association(Curie::new("GO", "6"), ida), &context);
+        assert_eq!(assoc.object.id, Curie::new("GO", "4"));
+        assert_eq!(result_set.results_for("gorule-0000008")[0].state, RuleState::Warning);
+    }
+
+    #[test]
+    fn test_rule_16_ic_requires_with_from() {
+        let context = ontology_context();
+        let ic = association(Curie::new("GO", "2"), Curie::new("ECO", "0000305"));
+        assert_eq!(run_state("gorule-0000016", ic.clone(), &context), RuleState::Warning);
+
+        let mut with_term = ic.clone();
+        with_term.evidence.with_support_from = with_from(&[Curie::new("GO", "0005515")]);
+        assert_eq!(run_state("gorule-0000016", with_term, &context), RuleState::Ok);
+
+        let mut without_term = ic;
+        without_term.evidence.with_support_from = with_from(&[Curie::new("UniProtKB", "P12345")]);
+        assert_eq!(run_state("gorule-0000016", without_term, &context), RuleState::Warning);
+
+        let ida = association(Curie::new("GO", "2"), Curie::new("ECO", "0000314"));
+        assert_eq!(run_state("gorule-0000016", ida, &context), RuleState::Ok);
+    }
+
+    #[test]
+    fn test_rule_17_ida_without_with_from() {
+        let context = ontology_context();
+        let ida = association(Curie::new("GO", "2"), Curie::new("ECO", "0000314"));
+        assert_eq!(run_state("gorule-0000017", ida.clone(), &context), RuleState::Ok);
+
+        let mut with_protein = ida;
+        with_protein.evidence.with_support_from = with_from(&[Curie::new("UniProtKB", "P12345")]);
+        let (_, result_set) = run_rules(with_protein, &context);
+        let result = &result_set.results_for("gorule-0000017")[0];
+        assert_eq!((result.state, result.entity.as_str()), (RuleState::Warning, "UniProtKB:P12345"));
+    }
+
+    #[test]
+    fn test_rule_18_ipi_requires_with_from() {
+        let context = ontology_context();
+        let ipi = association(Curie::new("GO", "2"), Curie::new("ECO", "0000353"));
+        assert_eq!(run_state("gorule-0000018", ipi.clone(), &context), RuleState::Warning);
+
+        let mut with_protein = ipi;
+        with_protein.evidence.with_support_from = with_from(&[Curie::new("UniProtKB", "P12345")]);
+        assert_eq!(run_state("gorule-0000018", with_protein, &context), RuleState::Ok);
+    }
+
+    #[test]
+    fn test_rule_28_aspect_matches_namespace() {
+        let context = ontology_context();
+        // GO:2 is in biological_process
+        let mut process = association(Curie::new("GO", "2"), Curie::new("ECO", "0000314"));
+        process.aspect = Some(Aspect::BioProcess);
+        assert_eq!(run_state("gorule-0000028", process.clone(), &context), RuleState::Ok);
+
+        let mut function = process.clone();
+        function.aspect = Some(Aspect::MolecularFunction);
+        let (repaired, result_set) = run_rules(function, &context);
+        assert_eq!(result_set.results_for("gorule-0000028")[0].state, RuleState::Repaired);
+        assert_eq!(repaired.aspect, Some(Aspect::BioProcess));
+
+        // GO:1 is repaired to GO:2 by gorule-0000020 first
+        let mut merged = process.clone();
+        merged.object.id = Curie::new("GO", "1");
+        assert_eq!(run_state("gorule-0000028", merged, &context), RuleState::Ok);
+
+        // GO:7 is in the ontology without a namespace
+        let mut no_namespace = process.clone();
+        no_namespace.object.id = Curie::new("GO", "7");
+        let (_, result_set) = run_rules(no_namespace, &context);
+        assert_eq!(result_set.results_for("gorule-0000028")[0].state, RuleState::Error);
+        assert_eq!(result_set.results_for("gorule-0000020")[0].state, RuleState::Ok);
+
+        // GO:9999999 isn't in the ontology, which only gorule-0000020 reports
+        let mut missing = process.clone();
+        missing.object.id = Curie::new("GO", "9999999");
+        let (_, result_set) = run_rules(missing, &context);
+        assert_eq!(result_set.results_for("gorule-0000028")[0].state, RuleState::Ok);
+        assert_eq!(result_set.results_for("gorule-0000020")[0].state, RuleState::Error);
+
+        process.aspect = None;
+        assert_eq!(run_state("gorule-0000028", process, &context), RuleState::Ok);
+    }
+
+    #[test]
+    fn test_rule_29_old_ieas() {
+        let today = chrono::NaiveDate::from_ymd_opt(2021, 6, 1).unwrap();
+        let context = ontology_context().add_today(today);
+        let dated = |evidence: &str, date: &str| {
+            let mut association = association(Curie::new("GO", "2"), Curie::new("ECO", evidence));
+            association.date = Date::from_gaf(date).unwrap();
+            association
+        };
+
+        assert_eq!(run_state("gorule-0000029", dated("0000501", "20200601"), &context), RuleState::Ok);
+        assert_eq!(run_state("gorule-0000029", dated("0000501", "20200531"), &context), RuleState::Warning);
+        // Only IEAs are checked
+        assert_eq!(run_state("gorule-0000029", dated("0000314", "20100101"), &context), RuleState::Ok);
+
+        let context = context.add_iea_max_age_days(30);
+        assert_eq!(run_state("gorule-0000029", dated("0000501", "20210415"), &context), RuleState::Warning);
+        assert_eq!(run_state("gorule-0000029", dated("0000501", "20210515"), &context), RuleState::Ok);
+    }
+
+    #[test]
+    fn test_rule_30_obsolete_gorefs() {
+        use crate::meta::goref::GoRef;
+        let goref = |id: &str, is_obsolete: bool| GoRef {
+            id: Curie::new("GO_REF", id),
+            title: "".into(),
+            is_obsolete,
+            evidence_codes: vec![]
+        };
+        let context = ontology_context().add_gorefs(vec![goref("0000002", false), goref("0000004", true)].into_iter().collect());
+        let referencing = |references: &[Curie]| {
+            let mut association = association(Curie::new("GO", "2"), Curie::new("ECO", "0000501"));
+            association.evidence.has_supporting_reference = ListField::new(references.to_vec());
+            association
+        };
+
+        assert_eq!(run_state("gorule-0000030", referencing(&[Curie::new("GO_REF", "0000002"), Curie::new("PMID", "21670302")]), &context), RuleState::Ok);
+        let (_, result_set) = run_rules(referencing(&[Curie::new("GO_REF", "0000004"), Curie::new("GO_REF", "9999999")]), &context);
+        let result = &result_set.results_for("gorule-0000030")[0];
+        assert_eq!((result.state, result.entity.as_str()), (RuleState::Warning, "GO_REF:0000004|GO_REF:9999999"));
+        // An unknown GO_REF alone is reported too
+        assert_eq!(run_state("gorule-0000030", referencing(&[Curie::new("GO_REF", "9999999")]), &context), RuleState::Warning);
+
+        // Nothing is checked without GO_REF metadata
+        assert_eq!(run_state("gorule-0000030", referencing(&[Curie::new("GO_REF", "0000004")]), &ontology_context()), RuleState::Ok);
+    }
+
+    #[test]
+    fn test_rule_50_sequence_similarity_with_gene_products() {
+        use crate::annotation::gpi::GeneProduct;
+        let product = GeneProduct {
+            id: Curie::new("MGI", "MGI:98961"),
+            symbol: NoSpaceString::new("Wnt7a"),
+            name: None,
+            synonyms: ListField::new(vec![]),
+            kind: PlainString("protein".into()),
+            taxon: Curie::new("taxon", "10090"),
+            parent: None,
+            xrefs: ListField::new(vec![])
+        };
+        let context = ontology_context().add_gene_products(vec![product].into_iter().collect());
+
+        let mut iss = association(Curie::new("GO", "2"), Curie::new("ECO", "0000250"));
+        iss.evidence.with_support_from = with_from(&[Curie::new("MGI", "MGI:98961")]);
+        assert_eq!(run_state("gorule-0000050", iss.clone(), &context), RuleState::Ok);
+
+        iss.evidence.with_support_from = with_from(&[Curie::new("MGI", "MGI:98961"), Curie::new("GO", "0005515")]);
+        let (_, result_set) = run_rules(iss.clone(), &context);
+        let result = &result_set.results_for("gorule-0000050")[0];
+        assert_eq!((result.state, result.entity.as_str()), (RuleState::Warning, "GO:0005515"));
+
+        // Other evidence isn't checked, and nothing is checked without a GPI
+        let mut ida = iss.clone();
+        ida.evidence.id = Curie::new("ECO", "0000314");
+        assert_eq!(run_state("gorule-0000050", ida, &context), RuleState::Ok);
+        assert_eq!(run_state("gorule-0000050", iss, &ontology_context()), RuleState::Ok);
+    }
+
+    #[test]
+    fn test_rule_02_states() {
+        let context = Context::default();
+        let protein_binding = association(Curie::new("GO", "0005515"), Curie::new("ECO", "0000314"));
+        assert_eq!(check(&Rule02, protein_binding.clone(), &context), (RuleState::Ok, true));
+
+        let mut negated = protein_binding;
+        negated.negated = true;
+        assert_eq!(check(&Rule02, negated, &context), (RuleState::Warning, false));
+    }
+
+    #[test]
+    fn test_rule_11_states() {
+        let context = Context::default();
+        let nd = Curie::new("ECO", "0000307");
+        let ida = Curie::new("ECO", "0000314");
+        assert_eq!(check(&Rule11::default(), association(Curie::new("GO", "0008150"), nd.clone()), &context), (RuleState::Ok, true));
+        assert_eq!(check(&Rule11::default(), association(Curie::new("GO", "0099175"), ida.clone()), &context), (RuleState::Ok, true));
+        assert_eq!(check(&Rule11::default(), association(Curie::new("GO", "0099175"), nd), &context), (RuleState::Error, false));
+        assert_eq!(check(&Rule11::default(), association(Curie::new("GO", "0008150"), ida), &context), (RuleState::Error, false));
+    }
+
+    #[test]
+    fn test_rule_20_states() {
+        let context = ontology_context();
+        let ida = Curie::new("ECO", "0000314");
+        assert_eq!(check(&Rule20, association(Curie::new("GO", "2"), ida.clone()), &context), (RuleState::Ok, true));
+        assert_eq!(check(&Rule20, association(Curie::new("GO", "1"), ida.clone()), &context), (RuleState::Repaired, false));
+        // GO:3 is obsolete with no replacement
+        assert_eq!(check(&Rule20, association(Curie::new("GO", "3"), ida.clone()), &context), (RuleState::Error, false));
+        assert_eq!(check(&Rule20, association(Curie::new("GO", "9999999"), ida.clone()), &context), (RuleState::Error, false));
+
+        let (assoc, result_set) = run_rules(association(Curie::new("GO", "3"), ida), &context);
+        assert!(result_set.line_skipped());
+        assert_eq!(assoc.object.id, Curie::new("GO", "3"));
+    }
+
     #[test]
     fn test_rule_20() {
         let before_assoc = GoAssociation::from((Subject::default(), Curie::new("BFO", "0000050"), Term::new(Curie::new("GO", "1"), None), Evidence::default(), Metadata::default(), Extensions::default()));
@@ -335,7 +1033,69 @@ mod test_rules {
         
         let (assoc, result_set) = run_rules(before_assoc, &context);
         
-        assert_eq!(result_set.all_results.get("gorule-0000020").unwrap().state, RuleState::Repaired);
+        assert_eq!(result_set.results_for("gorule-0000020")[0].state, RuleState::Repaired);
         assert_eq!(assoc.object.id, Curie::new("GO", "2"));
     }
+
+    #[test]
+    fn test_rule_registry_config() {
+        let repairable = GoAssociation::from((Subject::default(), Curie::new("BFO", "0000050"), Term::new(Curie::new("GO", "1"), None), Evidence::default(), Metadata::default(), Extensions::default()));
+        let mut context = Context::default().add_ontology(resource::load_ontology("resources/alt_id_ont.json").unwrap());
+
+        let config: RulesConfig = serde_json::from_str(r#"{
+            "rules": {"gorule-0000020": {"severity": "error"}, "2": {"enabled": false}},
+            "groups": {"MGI": {"gorule-20": {"severity": "warning"}}}
+        }"#).unwrap();
+        context.rules.configure(config).unwrap();
+
+        let (_, result_set) = run_rules(repairable.clone(), &context);
+        assert_eq!(result_set.results_for("gorule-0000020")[0].state, RuleState::Error);
+        assert!(!result_set.results_for("gorule-0000020")[0].valid);
+        assert!(result_set.results_for("gorule-0000002").is_empty());
+
+        let mut from_mgi = repairable;
+        from_mgi.provided_by = NoSpaceString::new("MGI");
+        let (_, result_set) = run_rules(from_mgi.clone(), &context);
+        assert_eq!(result_set.results_for("gorule-0000020")[0].state, RuleState::Warning);
+
+        context.rules.disable("gorule-0000020").unwrap();
+        let (assoc, result_set) = run_rules(from_mgi, &context);
+        assert!(result_set.results_for("gorule-0000020").is_empty());
+        assert_eq!(assoc.object.id, Curie::new("GO", "1"));
+
+        assert!(context.rules.set_severity("gorule-0000099", Severity::Error).is_err());
+    }
+
+    #[test]
+    fn test_rule_registry_metadata() {
+        let context = ontology_context();
+        let mut registry = RuleRegistry::default();
+        let metadata: Vec<RuleMetadata> = serde_yaml::from_str(r#"
+- id: GORULE:0000011
+  title: "ND annotations to root nodes only"
+  status: approved
+  fail_mode: soft
+- id: GORULE:0000005
+  title: "IEA, ISS, ISO, ISM, ISA, IBA, RCA, and IKR annotations are not allowed for protein binding"
+  status: approved
+  fail_mode: hard
+- id: GORULE:0000003
+  title: "Annotations to 'binding ; GO:0005488' and 'protein binding ; GO:0005515' should be made with IPI and an interactor in the 'with' field"
+  status: deprecated
+"#).unwrap();
+        registry.add_metadata(metadata);
+
+        let unimplemented: Vec<&str> = registry.unimplemented_rules().iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(unimplemented, vec!["GORULE:0000005"]);
+
+        // GO:2 is not a root, so ND is an error, but the upstream fail_mode is soft
+        let (_, result_set) = registry.run(association(Curie::new("GO", "2"), Curie::new("ECO", "0000307")), &context);
+        let rule_11 = &result_set.results_for("gorule-0000011")[0];
+        assert_eq!(rule_11.state, RuleState::Warning);
+        assert_eq!(rule_11.message, "ND annotations to root nodes only");
+
+        registry.set_severity("GORULE:0000011", Severity::Error).unwrap();
+        let (_, result_set) = registry.run(association(Curie::new("GO", "2"), Curie::new("ECO", "0000307")), &context);
+        assert_eq!(result_set.results_for("gorule-0000011")[0].state, RuleState::Error);
+    }
 }
@@ -1,33 +1,48 @@
+//!
+//! Validation of annotation lines, turning them into `GoAssociation`s and running the GO rules on them.
+//! 
+//! The `validate_*` functions validate a single line of each format, and `validate_record` validates a `RawRecord` of any
+//! format. `AnnotationValidator` validates a whole input, line by line, as an iterator while building up a `Report`.
+//! 
+
+use std::convert::{TryFrom, TryInto};
+use std::collections::VecDeque;
+use std::io::Read;
+
+use rayon::prelude::*;
+
 use crate::annotation::model;
-use crate::annotation::{RawGaf2_1Record, BaseGaf2_1Row};
+use crate::annotation::model::ConvertableAnnotation;
+use crate::annotation::error::{ParseError, ParseErrors};
+use crate::annotation::{AnnotationDocument, DocumentType, RawRecord, RawGaf2_1Record, BaseGaf2_1Row, RawGaf2_2Record, BaseGaf2_2Row};
+use crate::annotation::gpi::GeneProduct;
+use crate::annotation::gpad::{RawGpad1_2Record, BaseGpad1_2Row, RawGpad2_0Record, BaseGpad2_0Row};
 use crate::rules;
 use crate::rules::{ResultSet, RuleResult, RuleState};
 use crate::meta::Context;
 use crate::report::Report;
+use crate::resource::{self, AnnotationReader, ResourceError};
 
 
-/// TODO do we want to use this at all??
-// enum ValidateResult<T, R, W, E> {
-//     Ok(T),
-//     Repair(R),
-//     Warning(W),
-//     Error(E)
-// }
-
-// enum ResultMessage<A, M> {
-//     Just(A),
-//     Info(A, M)
-// }
+impl From<ParseError> for RuleResult {
+    fn from(error: ParseError) -> RuleResult {
+        RuleResult::new("gorule-0000001".to_string(), error.to_string(), error.value().unwrap_or("").to_string(), error.column_name().unwrap_or("").to_string(), false, RuleState::Error)
+    }
+}
 
-impl From<String> for RuleResult {
-    fn from(error: String) -> RuleResult {
-        RuleResult::new("gorule-0000001", &error, "", "", false, RuleState::Error)
+/// Every error from parsing a line, each as its own gorule-0000001 `RuleResult`.
+impl From<ParseErrors> for ResultSet {
+    fn from(errors: ParseErrors) -> ResultSet {
+        let mut result_set = ResultSet::new();
+        for error in errors {
+            result_set.add_result(RuleResult::from(error));
+        }
+        result_set
     }
 }
 
-/// TODO how to genericize?
-/// Validation takes a `RawGaf2_1Record` and a `Context` and produces a tuple with the original line, an optional
-/// `GoAssociation`, and the `ResultSet` from the rules.
+///
+/// Validation of any raw annotation line `R` that can be converted into a base annotation row `B`.
 /// 
 /// This works by invoking the `convert_raw` function in the `model` module and then running any `GoAssociation` produced
 /// into the Rules.
@@ -35,10 +50,14 @@ impl From<String> for RuleResult {
 /// If there are errors in parsing, these get wrapped up as a `ResultSet` as well.
 /// 
 /// The Optional `GoAssociation` is None if the original line could not be parsed or if there were any ERROR rules
-pub fn validate_gaf_2_1(line: RawGaf2_1Record, context: &Context) -> (RawGaf2_1Record, Option<model::GoAssociation>, ResultSet) {
+pub fn validate_raw<R, B>(line: R, context: &Context) -> (R, Option<model::GoAssociation>, ResultSet)
+    where
+        R: TryInto<B, Error=ParseErrors> + Clone,
+        B: ConvertableAnnotation {
+
     let original = line.clone();
 
-    let association = model::convert_raw::<RawGaf2_1Record, BaseGaf2_1Row>(line, context);
+    let association = model::convert_raw::<R, B>(line, context);
     let (results, maybe_assoc) = match association {
         Ok(assoc) => {
             let (assoc, result_set) = rules::run_rules(assoc, context);
@@ -50,24 +69,321 @@ pub fn validate_gaf_2_1(line: RawGaf2_1Record, context: &Context) -> (RawGaf2_1R
                 (result_set, Some(assoc))
             }
         },
-        Err(err) => {
-            let mut rule_set = ResultSet::new();
-            rule_set.add_result(RuleResult::from(err));
-            (rule_set, None)
-        }
+        Err(errors) => (ResultSet::from(errors), None)
     };
     (original, maybe_assoc, results)
 }
 
+/// Validation takes a `RawGaf2_1Record` and a `Context` and produces a tuple with the original line, an optional
+/// `GoAssociation`, and the `ResultSet` from the rules. See `validate_raw`.
+pub fn validate_gaf_2_1(line: RawGaf2_1Record, context: &Context) -> (RawGaf2_1Record, Option<model::GoAssociation>, ResultSet) {
+    validate_raw::<RawGaf2_1Record, BaseGaf2_1Row>(line, context)
+}
+
+/// The GAF 2.2 equivalent of `validate_gaf_2_1`.
+pub fn validate_gaf_2_2(line: RawGaf2_2Record, context: &Context) -> (RawGaf2_2Record, Option<model::GoAssociation>, ResultSet) {
+    validate_raw::<RawGaf2_2Record, BaseGaf2_2Row>(line, context)
+}
+
+/// The GPAD 1.2 equivalent of `validate_gaf_2_1`.
+pub fn validate_gpad_1_2(line: RawGpad1_2Record, context: &Context) -> (RawGpad1_2Record, Option<model::GoAssociation>, ResultSet) {
+    validate_raw::<RawGpad1_2Record, BaseGpad1_2Row>(line, context)
+}
+
+/// The GPAD 2.0 equivalent of `validate_gaf_2_1`.
+pub fn validate_gpad_2_0(line: RawGpad2_0Record, context: &Context) -> (RawGpad2_0Record, Option<model::GoAssociation>, ResultSet) {
+    validate_raw::<RawGpad2_0Record, BaseGpad2_0Row>(line, context)
+}
+
+///
+/// Validates a `RawRecord` of any format, dispatching to the matching `validate_*` function.
+/// 
+/// GPI lines describe gene products rather than annotations, so they are only checked for parse errors and never
+/// produce a `GoAssociation`.
+pub fn validate_record(line: RawRecord, context: &Context) -> (RawRecord, Option<model::GoAssociation>, ResultSet) {
+    match line {
+        RawRecord::Gaf2_1(raw) => {
+            let (original, association, result) = validate_gaf_2_1(raw, context);
+            (RawRecord::Gaf2_1(original), association, result)
+        },
+        RawRecord::Gaf2_2(raw) => {
+            let (original, association, result) = validate_gaf_2_2(raw, context);
+            (RawRecord::Gaf2_2(original), association, result)
+        },
+        RawRecord::Gpad1_2(raw) => {
+            let (original, association, result) = validate_gpad_1_2(raw, context);
+            (RawRecord::Gpad1_2(original), association, result)
+        },
+        RawRecord::Gpad2_0(raw) => {
+            let (original, association, result) = validate_gpad_2_0(raw, context);
+            (RawRecord::Gpad2_0(original), association, result)
+        },
+        RawRecord::Gpi1_2(raw) => {
+            let result = match GeneProduct::try_from(raw.clone()) {
+                Ok(_) => ResultSet::new(),
+                Err(errors) => ResultSet::from(errors)
+            };
+            (RawRecord::Gpi1_2(raw), None, result)
+        },
+        RawRecord::Gpi2_0(raw) => {
+            let result = match GeneProduct::try_from(raw.clone()) {
+                Ok(_) => ResultSet::new(),
+                Err(errors) => ResultSet::from(errors)
+            };
+            (RawRecord::Gpi2_0(raw), None, result)
+        }
+    }
+}
+
 ///
 /// Wraps `validate_gaf_2_1`, but takes an existing mutable Report. Results from `validate_gaf_2_1` are then added to the report,
-/// and the Option `GoAssociation` is returned along with the updated report.
+/// under the `line_number` of the line in the input, and the Option `GoAssociation` is returned along with the updated report.
 /// 
 /// Note: The report is being passed in directly, and not as a mutable reference. This is in part why we return it again
 /// as per rust's ownership rules. This function takes ownership of the report, and then gives ownership back by
 /// returning the given report.
-pub fn parse_and_report_gaf_2_1(line: RawGaf2_1Record, context: &Context, mut report: Report) -> (Option<model::GoAssociation>, Report) {
+pub fn parse_and_report_gaf_2_1(line_number: u64, line: RawGaf2_1Record, context: &Context, mut report: Report) -> (Option<model::GoAssociation>, Report) {
     let (original, association, result) = validate_gaf_2_1(line, context);
-    report.add_result(original, result);
+    report.add_result(line_number, original, result);
     (association, report)
 }
+
+/// The GAF 2.2 equivalent of `parse_and_report_gaf_2_1`.
+pub fn parse_and_report_gaf_2_2(line_number: u64, line: RawGaf2_2Record, context: &Context, mut report: Report) -> (Option<model::GoAssociation>, Report) {
+    let (original, association, result) = validate_gaf_2_2(line, context);
+    report.add_result(line_number, original, result);
+    (association, report)
+}
+
+/// The GPAD 1.2 equivalent of `parse_and_report_gaf_2_1`.
+pub fn parse_and_report_gpad_1_2(line_number: u64, line: RawGpad1_2Record, context: &Context, mut report: Report) -> (Option<model::GoAssociation>, Report) {
+    let (original, association, result) = validate_gpad_1_2(line, context);
+    report.add_result(line_number, original, result);
+    (association, report)
+}
+
+/// The GPAD 2.0 equivalent of `parse_and_report_gaf_2_1`.
+pub fn parse_and_report_gpad_2_0(line_number: u64, line: RawGpad2_0Record, context: &Context, mut report: Report) -> (Option<model::GoAssociation>, Report) {
+    let (original, association, result) = validate_gpad_2_0(line, context);
+    report.add_result(line_number, original, result);
+    (association, report)
+}
+
+/// A validated line: the line number in the input, the original line, the `GoAssociation` if the line is valid, and the
+/// results of validating it.
+pub type ValidatedLine = (u64, RawRecord, Option<model::GoAssociation>, ResultSet);
+
+/// A line validated on its own, before it is added to the report. Lines with the wrong number of columns can't be made
+/// into a `RawRecord`, so they are kept as their text for the report.
+type CheckedLine = (u64, Result<(RawRecord, Option<model::GoAssociation>), String>, ResultSet);
+
+/// How many lines are read and validated together in parallel mode, if not set with `AnnotationValidator::parallel`.
+pub const DEFAULT_CHUNK_SIZE: usize = 10_000;
+
+///
+/// Validates annotations one line at a time as an `Iterator` of `ValidatedLine`s, adding each line's results to a `Report`
+/// as it goes. Lines are only read as the iterator is advanced, so large inputs can be processed lazily.
+/// 
+/// Lines with the wrong number of columns for the document type can't be made into a `RawRecord`, so they are added to
+/// the report as parse errors but not yielded. Errors reading the input are yielded as `csv::Error`s.
+/// 
+/// By default each line is validated as it is read. With `parallel`, lines are read in chunks and each chunk is validated
+/// on the rayon thread pool, sharing the `Context`. Lines are still yielded and reported in the order of the input.
+/// 
+/// ```ignore
+/// let mut validator = AnnotationValidator::from_reader("fb.gaf", File::open("fb.gaf")?, &context)?.parallel(10_000);
+/// for line in &mut validator {
+///     let (line_number, original, association, results) = line?;
+/// }
+/// let report = validator.into_report();
+/// ```
+pub struct AnnotationValidator<'a> {
+    document: AnnotationDocument<model::GoAssociation>,
+    records: AnnotationReader,
+    context: &'a Context,
+    report: Report,
+    /// Lines per chunk in parallel mode, or None to validate each line as it is read
+    chunk_size: Option<usize>,
+    /// Validated lines from the current chunk that haven't been yielded yet
+    pending: VecDeque<Result<ValidatedLine, csv::Error>>
+}
+
+impl<'a> AnnotationValidator<'a> {
+    /// Makes a validator from the name, document, and reader returned by `resource::read_annotations`.
+    pub fn new((name, document, reader): (String, AnnotationDocument<model::GoAssociation>, AnnotationReader), context: &'a Context) -> AnnotationValidator<'a> {
+        let mut report = Report::new(name);
+        report.add_unimplemented_rules(&context.rules);
+        AnnotationValidator {
+            document,
+            records: reader,
+            context,
+            report,
+            chunk_size: None,
+            pending: VecDeque::new()
+        }
+    }
+
+    /// Reads the header from `reader` with `resource::read_annotations` and makes a validator for the rest of it.
+    pub fn from_reader<S: Into<String>, R: Read + 'static>(name: S, reader: R, context: &'a Context) -> Result<AnnotationValidator<'a>, ResourceError> {
+        resource::read_annotations(name.into(), reader).map(|annotations| AnnotationValidator::new(annotations, context))
+    }
+
+    /// Validates `chunk_size` lines at a time in parallel on the rayon thread pool.
+    pub fn parallel(mut self, chunk_size: usize) -> AnnotationValidator<'a> {
+        self.chunk_size = Some(chunk_size.max(1));
+        self
+    }
+
+    pub fn document(&self) -> &AnnotationDocument<model::GoAssociation> {
+        &self.document
+    }
+
+    pub fn document_type(&self) -> DocumentType {
+        self.document.document_type()
+    }
+
+    pub fn context(&self) -> &'a Context {
+        self.context
+    }
+
+    /// The report of the lines validated so far.
+    pub fn report(&self) -> &Report {
+        &self.report
+    }
+
+    pub fn into_report(self) -> Report {
+        self.report
+    }
+
+    ///
+    /// Reads and validates the next chunk of lines, queueing them in `pending` and adding them to the validator's report.
+    /// A read error ends the chunk and is queued after the lines before it. False if there was nothing left to read.
+    fn next_chunk(&mut self) -> bool {
+        let chunk_size = self.chunk_size.unwrap_or(1);
+        let mut records = Vec::with_capacity(chunk_size);
+        let mut error = None;
+        for next in (&mut self.records).take(chunk_size) {
+            match next {
+                Ok(record) => records.push(record),
+                Err(err) => {
+                    error = Some(err);
+                    break;
+                }
+            }
+        }
+
+        if records.is_empty() && error.is_none() {
+            return false;
+        }
+
+        let document = &self.document;
+        let context = self.context;
+        let checked: Vec<CheckedLine> = if self.chunk_size.is_some() {
+            records.par_iter().map(|record| check_record(document, record, context)).collect()
+        } else {
+            records.iter().map(|record| check_record(document, record, context)).collect()
+        };
+
+        for (line_number, line, result) in checked {
+            match line {
+                Ok((original, association)) => {
+                    self.report.add_result(line_number, &original, result.clone());
+                    self.pending.push_back(Ok((line_number, original, association, result)));
+                },
+                Err(text) => self.report.add_result(line_number, text, result)
+            }
+        }
+        if let Some(err) = error {
+            self.pending.push_back(Err(err));
+        }
+        true
+    }
+}
+
+/// Validates a single `record` from `document`.
+fn check_record(document: &AnnotationDocument<model::GoAssociation>, record: &csv::StringRecord, context: &Context) -> CheckedLine {
+    let line_number = resource::line_number(record);
+    match RawRecord::from_record(record, document.document_type()) {
+        Ok(raw) => {
+            let (original, association, result) = validate_record(raw, context);
+            (line_number, Ok((original, association)), result)
+        },
+        Err(err) => {
+            let mut result = ResultSet::new();
+            result.add_result(RuleResult::from(err));
+            (line_number, Err(record.iter().collect::<Vec<&str>>().join("\t")), result)
+        }
+    }
+}
+
+impl<'a> Iterator for AnnotationValidator<'a> {
+    type Item = Result<ValidatedLine, csv::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(line) = self.pending.pop_front() {
+                return Some(line);
+            }
+            if !self.next_chunk() {
+                return None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::annotation::GafVersion;
+    use std::io;
+
+    #[test]
+    fn test_annotation_validator() {
+        let gaf = "!gaf-version: 2.2\n\
+            MGI\tMGI:98961\tWnt7a\tinvolved_in\tGO:2\tPMID:21670302\tIMP\t\tP\t\t\tprotein\ttaxon:10090\t20180711\tSynGO\t\t\n\
+            MGI\tMGI:98961\n\
+            MGI\tMGI:98961\tWnt7a\tnot_a_relation\tGO:2\tPMID:21670302\tIMP\t\tP\t\t\tprotein\ttaxon:10090\t20180711\tSynGO\t\t\n";
+        let context = Context::default().add_ontology(resource::load_ontology("resources/alt_id_ont.json").unwrap());
+        let mut validator = AnnotationValidator::from_reader("test", gaf.as_bytes(), &context).unwrap();
+        assert_eq!(validator.document_type(), DocumentType::Gaf(GafVersion::Gaf2_2));
+
+        let lines: Vec<ValidatedLine> = (&mut validator).map(Result::unwrap).collect();
+        assert_eq!(lines.len(), 2);
+
+        let (line_number, _, association, _) = &lines[0];
+        assert_eq!(*line_number, 2);
+        assert!(association.is_some());
+
+        let (line_number, _, association, results) = &lines[1];
+        assert_eq!(*line_number, 4);
+        assert!(association.is_none());
+        assert!(results.line_skipped());
+
+        let report = validator.into_report();
+        assert_eq!(report.to_string().lines().take(4).collect::<Vec<&str>>(), vec!["## test Report", "* lines: 3", "* skipped: 2", "* valid: 1"]);
+    }
+
+    #[test]
+    fn test_parallel_validator_keeps_order() {
+        let line = "MGI\tMGI:98961\tWnt7a\tinvolved_in\tGO:2\tPMID:21670302\tIMP\t\tP\t\t\tprotein\ttaxon:10090\t20180711\tSynGO\t\t\n";
+        let bad = "MGI\tMGI:98961\tWnt7a\tnot_a_relation\tGO:2\tPMID:21670302\tIMP\t\tP\t\t\tprotein\ttaxon:10090\t20180711\tSynGO\t\t\n";
+        let mut gaf = String::from("!gaf-version: 2.2\n");
+        for i in 0..100 {
+            gaf.push_str(if i % 3 == 0 { bad } else { line });
+        }
+        let context = Context::default().add_ontology(resource::load_ontology("resources/alt_id_ont.json").unwrap());
+
+        let sequential: Vec<ValidatedLine> = AnnotationValidator::from_reader("test", io::Cursor::new(gaf.clone()), &context).unwrap()
+            .map(Result::unwrap)
+            .collect();
+        let mut validator = AnnotationValidator::from_reader("test", io::Cursor::new(gaf), &context).unwrap().parallel(7);
+        let parallel: Vec<ValidatedLine> = (&mut validator).map(Result::unwrap).collect();
+
+        let line_numbers: Vec<u64> = parallel.iter().map(|(n, _, _, _)| *n).collect();
+        assert_eq!(line_numbers, (2..102).collect::<Vec<u64>>());
+        for ((_, _, seq_assoc, _), (_, _, par_assoc, _)) in sequential.iter().zip(parallel.iter()) {
+            assert_eq!(seq_assoc, par_assoc);
+        }
+        assert_eq!(validator.into_report().to_string().lines().nth(2), Some("* skipped: 34"));
+    }
+}